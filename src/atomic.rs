@@ -0,0 +1,196 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use histogram::*;
+
+pub fn new_atomic_histogram(_highest_trackable_value: i64,
+                            _number_of_significant_digits: i32)
+                            -> AtomicHistogram {
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits);
+    match AtomicHistogram::from_config(&config) {
+        Ok(histogram) => histogram,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+/// A histogram that can be recorded into concurrently from many threads.
+///
+/// Counts, the total count and the min/max values are updated with atomic operations, so
+/// recording only requires a shared reference. The covered range is fixed at construction;
+/// use `snapshot` to obtain a `Histogram` for querying and serialisation.
+pub struct AtomicHistogram {
+    config: HistogramConfig,
+    layout: BucketLayout,
+    counts: Box<[AtomicI64]>,
+    total_count: AtomicI64,
+    max_value: AtomicI64,
+    min_non_zero_value: AtomicI64,
+}
+
+impl AtomicHistogram {
+    pub fn from_config(config: &HistogramConfig) -> Result<AtomicHistogram, CreationError> {
+        if config.auto_resize {
+            return Err(CreationError::AutoResizeNotSupported);
+        }
+        let layout = config.build_layout()?;
+        let counts_array_length = layout.counts_array_length_needed(config.highest_trackable_value);
+
+        Ok(AtomicHistogram {
+            config: *config,
+            layout,
+            counts: (0..counts_array_length).map(|_| AtomicI64::new(0)).collect(),
+            total_count: AtomicI64::new(0),
+            max_value: AtomicI64::new(0),
+            min_non_zero_value: AtomicI64::new(i64::MAX),
+        })
+    }
+
+    pub fn record_value(&self, value: i64) {
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_expected_interval(&self,
+                                               value: i64,
+                                               expected_interval_between_value_samples: i64) {
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&self, value: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(1, value)
+    }
+
+    pub fn try_record_value_with_count(&self, value: i64, count: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(count, value)
+    }
+
+    pub fn try_record_value_with_expected_interval(&self,
+                                                   value: i64,
+                                                   expected_interval_between_value_samples: i64)
+                                                   -> Result<(), RecordError> {
+        record_with_expected_interval(value,
+                                      expected_interval_between_value_samples,
+                                      |value_to_record| self.record_count_at_value(1, value_to_record))
+    }
+
+    pub fn get_total_count(&self) -> i64 {
+        self.total_count.load(Ordering::Relaxed)
+    }
+
+    pub fn get_max_value(&self) -> i64 {
+        self.max_value.load(Ordering::Relaxed)
+    }
+
+    pub fn get_min_value(&self) -> i64 {
+        let min_non_zero_value = self.min_non_zero_value.load(Ordering::Relaxed);
+        if min_non_zero_value == i64::MAX {
+            0
+        } else {
+            min_non_zero_value
+        }
+    }
+
+    pub fn get_count_at_value(&self, value: i64) -> i64 {
+        let index = self.layout.counts_array_index(value);
+        if index < 0 || index as usize >= self.counts.len() {
+            return 0;
+        }
+        self.counts[index as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn get_lowest_discernible_value(&self) -> i64 {
+        self.config.lowest_discernible_value
+    }
+
+    pub fn get_highest_trackable_value(&self) -> i64 {
+        self.config.highest_trackable_value
+    }
+
+    pub fn get_number_of_significant_value_digits(&self) -> i32 {
+        self.config.number_of_significant_digits
+    }
+
+    /// Zeroes each count in turn, then the total count and min/max values. Nothing stops other
+    /// threads from recording meanwhile, so a value recorded during the reset may be kept in its
+    /// count but dropped from the total count.
+    pub fn reset(&self) {
+        for count in self.counts.iter() {
+            count.store(0, Ordering::Relaxed);
+        }
+        self.total_count.store(0, Ordering::Relaxed);
+        self.max_value.store(0, Ordering::Relaxed);
+        self.min_non_zero_value.store(i64::MAX, Ordering::Relaxed);
+    }
+
+    /// Builds a new `Histogram` from the current counts.
+    ///
+    /// Each count is loaded separately while other threads may still be recording, so the
+    /// snapshot need not match the histogram at any single instant. Its total count and min/max
+    /// values are recomputed from the loaded counts, rather than read from this histogram, so
+    /// that they agree with them.
+    pub fn snapshot(&self) -> Histogram {
+        let mut histogram = match self.config.build() {
+            Ok(histogram) => histogram,
+            Err(e) => panic!("Invalid histogram configuration: {}", e),
+        };
+        self.copy_into(&mut histogram);
+        histogram
+    }
+
+    /// Replaces the contents of `target` with the current counts, growing `target` if needed.
+    ///
+    /// Panics if `target` does not share this histogram's lowest discernible value and number of
+    /// significant digits.
+    pub fn copy_into(&self, target: &mut Histogram) {
+        if target.get_lowest_discernible_value() != self.config.lowest_discernible_value ||
+           target.get_number_of_significant_value_digits() != self.config.number_of_significant_digits {
+            panic!("Cannot copy into a histogram with a different configuration");
+        }
+        target.reset();
+        if (target.get_counts_array_length() as usize) < self.counts.len() {
+            target.resize(self.config.highest_trackable_value);
+        }
+        for (index, count) in self.counts.iter().enumerate() {
+            let count = count.load(Ordering::Relaxed);
+            if count != 0 {
+                target.set_count_at_index(index as i32, count);
+            }
+        }
+        target.establish_internal_tracking_values(self.counts.len() as i32);
+    }
+
+    fn record_count_at_value(&self, count: i64, value: i64) -> Result<(), RecordError> {
+        if value < 0 {
+            return Err(RecordError::NegativeValue(value));
+        }
+        if count < 0 {
+            return Err(RecordError::NegativeCount(count));
+        }
+        let counts_index = self.layout.counts_array_index(value);
+        if counts_index as usize >= self.counts.len() {
+            return Err(RecordError::ValueOutOfRange(value));
+        }
+        if self.total_count
+               .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| total.checked_add(count))
+               .is_err() {
+            return Err(RecordError::CountOverflow);
+        }
+        if self.counts[counts_index as usize]
+               .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| current.checked_add(count))
+               .is_err() {
+            self.total_count.fetch_sub(count, Ordering::Relaxed);
+            return Err(RecordError::CountOverflow);
+        }
+        self.update_min_and_max(value);
+        Ok(())
+    }
+
+    fn update_min_and_max(&self, value: i64) {
+        self.max_value.fetch_max(value, Ordering::Relaxed);
+        if value != 0 {
+            self.min_non_zero_value.fetch_min(value, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::atomic::{AtomicI64, AtomicPtr, Ordering};
+use histogram::*;
+use phaser::*;
+
+type Counts = Vec<AtomicI64>;
+
+pub fn new_concurrent_histogram(_highest_trackable_value: i64,
+                                _number_of_significant_digits: i32)
+                                -> ConcurrentHistogram {
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits);
+    match ConcurrentHistogram::from_config(&config) {
+        Ok(histogram) => histogram,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+/// A port of HdrHistogram's `ConcurrentHistogram`.
+///
+/// Recording is wait-free from any number of threads, and remains correct while the counts
+/// array is auto-resized or copied by a reader. Counts are split across an active and an
+/// inactive array; writers only ever increment the active array, while readers and resizing
+/// operate under the reader lock of a `WriterReaderPhaser` and sum both arrays.
+pub struct ConcurrentHistogram {
+    config: HistogramConfig,
+    layout: BucketLayout,
+    phaser: WriterReaderPhaser,
+    active_counts: AtomicPtr<Counts>,
+    inactive_counts: AtomicPtr<Counts>,
+    highest_trackable_value: AtomicI64,
+    total_count: AtomicI64,
+    max_value: AtomicI64,
+    min_non_zero_value: AtomicI64,
+}
+
+impl ConcurrentHistogram {
+    pub fn from_config(config: &HistogramConfig) -> Result<ConcurrentHistogram, CreationError> {
+        let layout = config.build_layout()?;
+        let counts_array_length = layout.counts_array_length_needed(config.highest_trackable_value);
+
+        Ok(ConcurrentHistogram {
+            config: *config,
+            layout,
+            phaser: WriterReaderPhaser::new(),
+            active_counts: AtomicPtr::new(new_counts(counts_array_length as usize)),
+            inactive_counts: AtomicPtr::new(new_counts(counts_array_length as usize)),
+            highest_trackable_value: AtomicI64::new(config.highest_trackable_value),
+            total_count: AtomicI64::new(0),
+            max_value: AtomicI64::new(0),
+            min_non_zero_value: AtomicI64::new(i64::MAX),
+        })
+    }
+
+    pub fn record_value(&self, value: i64) {
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_expected_interval(&self,
+                                               value: i64,
+                                               expected_interval_between_value_samples: i64) {
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&self, value: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(1, value)
+    }
+
+    pub fn try_record_value_with_count(&self, value: i64, count: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(count, value)
+    }
+
+    pub fn try_record_value_with_expected_interval(&self,
+                                                   value: i64,
+                                                   expected_interval_between_value_samples: i64)
+                                                   -> Result<(), RecordError> {
+        record_with_expected_interval(value,
+                                      expected_interval_between_value_samples,
+                                      |value_to_record| self.record_count_at_value(1, value_to_record))
+    }
+
+    pub fn set_auto_resize(&mut self, auto_resize: bool) {
+        self.config.auto_resize = auto_resize;
+    }
+
+    pub fn is_auto_resize(&self) -> bool {
+        self.config.auto_resize
+    }
+
+    pub fn get_total_count(&self) -> i64 {
+        self.total_count.load(Ordering::Relaxed)
+    }
+
+    pub fn get_max_value(&self) -> i64 {
+        self.max_value.load(Ordering::Relaxed)
+    }
+
+    pub fn get_min_value(&self) -> i64 {
+        let min_non_zero_value = self.min_non_zero_value.load(Ordering::Relaxed);
+        if min_non_zero_value == i64::MAX {
+            0
+        } else {
+            min_non_zero_value
+        }
+    }
+
+    pub fn get_count_at_value(&self, value: i64) -> i64 {
+        let index = self.layout.counts_array_index(value);
+        let _reader = self.phaser.reader_lock();
+        let (active_counts, inactive_counts) = unsafe { self.counts() };
+        if index < 0 || index as usize >= active_counts.len() {
+            return 0;
+        }
+        active_counts[index as usize].load(Ordering::Relaxed) +
+        inactive_counts[index as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn get_lowest_discernible_value(&self) -> i64 {
+        self.config.lowest_discernible_value
+    }
+
+    pub fn get_highest_trackable_value(&self) -> i64 {
+        self.highest_trackable_value.load(Ordering::Relaxed)
+    }
+
+    pub fn get_number_of_significant_value_digits(&self) -> i32 {
+        self.config.number_of_significant_digits
+    }
+
+    /// Clears all recorded values.
+    ///
+    /// Writers are switched onto the already cleared inactive counts, and the phaser waits for any
+    /// still incrementing the previously active counts before those are cleared too, so no count
+    /// is left half-cleared. The total count and min/max values are reset last, so a value
+    /// recorded during the reset may be kept in its count but dropped from the total count.
+    pub fn reset(&self) {
+        let reader = self.phaser.reader_lock();
+        unsafe {
+            clear_counts(&*self.inactive_counts.load(Ordering::Acquire));
+        }
+        self.swap_counts();
+        reader.flip_phase();
+        unsafe {
+            clear_counts(&*self.inactive_counts.load(Ordering::Acquire));
+        }
+        self.total_count.store(0, Ordering::Relaxed);
+        self.max_value.store(0, Ordering::Relaxed);
+        self.min_non_zero_value.store(i64::MAX, Ordering::Relaxed);
+    }
+
+    /// Builds a new `Histogram` from the sum of the active and inactive counts.
+    ///
+    /// The counts are read under the reader lock, so a concurrent resize or reset cannot swap the
+    /// arrays part way through, though writers may still increment the active counts. The total
+    /// count and min/max values are recomputed from the summed counts.
+    pub fn snapshot(&self) -> Histogram {
+        let mut histogram = match self.config.build() {
+            Ok(histogram) => histogram,
+            Err(e) => panic!("Invalid histogram configuration: {}", e),
+        };
+        self.copy_into(&mut histogram);
+        histogram
+    }
+
+    /// Replaces the contents of `target` with the recorded counts, growing `target` if needed.
+    ///
+    /// Panics if `target` does not share this histogram's lowest discernible value and number of
+    /// significant digits.
+    pub fn copy_into(&self, target: &mut Histogram) {
+        if target.get_lowest_discernible_value() != self.config.lowest_discernible_value ||
+           target.get_number_of_significant_value_digits() != self.config.number_of_significant_digits {
+            panic!("Cannot copy into a histogram with a different configuration");
+        }
+        let _reader = self.phaser.reader_lock();
+        let (active_counts, inactive_counts) = unsafe { self.counts() };
+        target.reset();
+        if (target.get_counts_array_length() as usize) < active_counts.len() {
+            target.resize(self.highest_trackable_value.load(Ordering::Relaxed));
+        }
+        for index in 0..active_counts.len() {
+            let count = active_counts[index].load(Ordering::Relaxed) +
+                        inactive_counts[index].load(Ordering::Relaxed);
+            if count != 0 {
+                target.set_count_at_index(index as i32, count);
+            }
+        }
+        target.establish_internal_tracking_values(active_counts.len() as i32);
+    }
+
+    fn record_count_at_value(&self, count: i64, value: i64) -> Result<(), RecordError> {
+        if value < 0 {
+            return Err(RecordError::NegativeValue(value));
+        }
+        if count < 0 {
+            return Err(RecordError::NegativeCount(count));
+        }
+        let counts_index = self.layout.counts_array_index(value) as usize;
+        if self.total_count
+               .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| total.checked_add(count))
+               .is_err() {
+            return Err(RecordError::CountOverflow);
+        }
+        loop {
+            let recorded = {
+                let _critical_section = self.phaser.writer_critical_section_enter();
+                let active_counts = unsafe { &*self.active_counts.load(Ordering::Acquire) };
+                if counts_index < active_counts.len() {
+                    Some(active_counts[counts_index]
+                             .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| current.checked_add(count))
+                             .is_ok())
+                } else {
+                    None
+                }
+            };
+            match recorded {
+                Some(true) => break,
+                Some(false) => {
+                    self.total_count.fetch_sub(count, Ordering::Relaxed);
+                    return Err(RecordError::CountOverflow);
+                }
+                None if self.config.auto_resize => self.resize(value),
+                None => {
+                    self.total_count.fetch_sub(count, Ordering::Relaxed);
+                    return Err(RecordError::ValueOutOfRange(value));
+                }
+            }
+        }
+        self.max_value.fetch_max(value, Ordering::Relaxed);
+        if value != 0 {
+            self.min_non_zero_value.fetch_min(value, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn resize(&self, new_highest_trackable_value: i64) {
+        let reader = self.phaser.reader_lock();
+        let new_counts_array_length =
+            self.layout.counts_array_length_needed(new_highest_trackable_value) as usize;
+        if new_counts_array_length <= unsafe { (*self.active_counts.load(Ordering::Acquire)).len() } {
+            // another thread resized while we waited for the reader lock
+            return;
+        }
+
+        // no writer uses the inactive counts, so they can be replaced immediately
+        self.grow_inactive_counts(new_counts_array_length);
+        self.swap_counts();
+        // wait until no writer can still be incrementing the previously active counts
+        reader.flip_phase();
+        self.grow_inactive_counts(new_counts_array_length);
+
+        let highest_trackable_value =
+            self.layout.highest_equivalent_value(self.layout.value_from_index(new_counts_array_length as i32 - 1));
+        self.highest_trackable_value.store(highest_trackable_value, Ordering::Relaxed);
+    }
+
+    fn grow_inactive_counts(&self, new_length: usize) {
+        let grown = new_counts(new_length);
+        let old = self.inactive_counts.swap(grown, Ordering::AcqRel);
+        unsafe {
+            let (old_counts, grown_counts) = (&*old, &*grown);
+            for (index, count) in old_counts.iter().enumerate() {
+                grown_counts[index].store(count.load(Ordering::Relaxed), Ordering::Relaxed);
+            }
+            drop(Box::from_raw(old));
+        }
+    }
+
+    fn swap_counts(&self) {
+        let active = self.active_counts.load(Ordering::Acquire);
+        let inactive = self.inactive_counts.load(Ordering::Acquire);
+        self.active_counts.store(inactive, Ordering::Release);
+        self.inactive_counts.store(active, Ordering::Release);
+    }
+
+    /// Callers must hold the reader lock, which prevents the arrays from being replaced.
+    unsafe fn counts(&self) -> (&Counts, &Counts) {
+        (&*self.active_counts.load(Ordering::Acquire), &*self.inactive_counts.load(Ordering::Acquire))
+    }
+}
+
+impl Drop for ConcurrentHistogram {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(*self.active_counts.get_mut()));
+            drop(Box::from_raw(*self.inactive_counts.get_mut()));
+        }
+    }
+}
+
+fn new_counts(length: usize) -> *mut Counts {
+    Box::into_raw(Box::new((0..length).map(|_| AtomicI64::new(0)).collect()))
+}
+
+fn clear_counts(counts: &Counts) {
+    for count in counts.iter() {
+        count.store(0, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+
+/// The type used to store the count recorded at each index of a histogram.
+///
+/// Narrower counters reduce the memory footprint of a histogram at the cost of a lower maximum
+/// count per value. Counts are exchanged with the histogram as `i64`, and a count that cannot be
+/// represented by the counter type is reported as an overflow.
+pub trait Counter: Copy + PartialEq + fmt::Debug {
+    fn zero() -> Self;
+
+    fn as_i64(self) -> i64;
+
+    /// Returns `None` if `value` is negative or too large for this counter type
+    fn from_i64(value: i64) -> Option<Self>;
+}
+
+macro_rules! impl_counter {
+    ($t:ty) => {
+        impl Counter for $t {
+            fn zero() -> $t {
+                0
+            }
+
+            fn as_i64(self) -> i64 {
+                self as i64
+            }
+
+            fn from_i64(value: i64) -> Option<$t> {
+                if value < 0 || value as u64 > <$t>::MAX as u64 {
+                    None
+                } else {
+                    Some(value as $t)
+                }
+            }
+        }
+    }
+}
+
+impl_counter!(u8);
+impl_counter!(u16);
+impl_counter!(u32);
+impl_counter!(u64);
+
+/// The storage used for a histogram's counts array.
+///
+/// Every index below `length()` can be read, whether or not a count has been stored there; unset
+/// indexes hold a zero count.
+pub trait CountsArray<T: Counter> {
+    fn with_length(length: usize) -> Self;
+
+    fn length(&self) -> usize;
+
+    fn get(&self, index: usize) -> T;
+
+    fn set(&mut self, index: usize, count: T);
+
+    /// Grows or shrinks the array, filling any new indexes with zero counts
+    fn resize(&mut self, length: usize);
+
+    /// Sets every count to zero
+    fn clear(&mut self);
+}
+
+impl<T: Counter> CountsArray<T> for Vec<T> {
+    fn with_length(length: usize) -> Vec<T> {
+        vec![T::zero(); length]
+    }
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, index: usize) -> T {
+        self[index]
+    }
+
+    fn set(&mut self, index: usize, count: T) {
+        self[index] = count;
+    }
+
+    fn resize(&mut self, length: usize) {
+        Vec::resize(self, length, T::zero());
+    }
+
+    fn clear(&mut self) {
+        for count in self.iter_mut() {
+            *count = T::zero();
+        }
+    }
+}
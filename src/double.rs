@@ -0,0 +1,346 @@
+use std::error;
+use std::fmt;
+use encoding::*;
+use histogram::*;
+
+const DOUBLE_HISTOGRAM_COOKIE: i32 = 0x0c72124e;
+
+pub fn new_double_histogram(_highest_to_lowest_value_ratio: i64,
+                            _number_of_significant_digits: i32)
+                            -> DoubleHistogram {
+    match DoubleHistogram::from_dynamic_range(_highest_to_lowest_value_ratio, _number_of_significant_digits) {
+        Ok(histogram) => histogram,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+pub fn deserialise_double_histogram(byte_array: &[u8], offset: i32) -> Option<DoubleHistogram> {
+    if get_i32(byte_array, offset) != DOUBLE_HISTOGRAM_COOKIE {
+        return None;
+    }
+    let number_of_significant_digits = get_i32(byte_array, offset + I32_BYTES);
+    let highest_to_lowest_value_ratio = get_i64(byte_array, offset + 2 * I32_BYTES);
+
+    let mut histogram =
+        DoubleHistogram::from_dynamic_range(highest_to_lowest_value_ratio, number_of_significant_digits).ok()?;
+    let values = deserialise_histogram(byte_array, offset + 2 * I32_BYTES + I64_BYTES)?;
+    if values.get_lowest_discernible_value() != histogram.values.get_lowest_discernible_value() ||
+       values.get_highest_trackable_value() != histogram.values.get_highest_trackable_value() ||
+       values.get_number_of_significant_value_digits() != number_of_significant_digits {
+        return None;
+    }
+    let lowest_value_in_auto_range =
+        values.get_integer_to_double_value_conversion_ratio() * histogram.lowest_tracking_integer_value as f64;
+    histogram.values = values;
+    histogram.set_trackable_value_range(lowest_value_in_auto_range);
+
+    Some(histogram)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoubleRecordError {
+    /// The value cannot be covered without shifting the histogram's dynamic range away from
+    /// already recorded values
+    ValueOutOfRange(f64),
+    /// Negative values cannot be recorded
+    NegativeValue(f64),
+    /// Negative counts cannot be recorded
+    NegativeCount(i64),
+    /// Recording the count would overflow the count at the value's index, or the total count
+    CountOverflow,
+}
+
+impl fmt::Display for DoubleRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DoubleRecordError::ValueOutOfRange(value) => {
+                write!(f, "value {} cannot be covered by the histogram's dynamic range", value)
+            }
+            DoubleRecordError::NegativeValue(value) => write!(f, "cannot record negative value {}", value),
+            DoubleRecordError::NegativeCount(count) => write!(f, "cannot record negative count {}", count),
+            DoubleRecordError::CountOverflow => write!(f, "recording would overflow the histogram's counts"),
+        }
+    }
+}
+
+impl error::Error for DoubleRecordError {}
+
+/// A port of HdrHistogram's `DoubleHistogram`.
+///
+/// Values are recorded into an integer `Histogram` after multiplying them by a conversion ratio.
+/// The histogram covers a fixed dynamic range (the ratio between the highest and lowest values it
+/// can track at the requested precision), and moves that range up or down by powers of two as
+/// values outside the currently covered range are recorded.
+pub struct DoubleHistogram {
+    configured_highest_to_lowest_value_ratio: i64,
+    internal_highest_to_lowest_value_ratio: i64,
+    lowest_tracking_integer_value: i64,
+    current_lowest_value_in_auto_range: f64,
+    current_highest_value_limit_in_auto_range: f64,
+    double_to_integer_value_conversion_ratio: f64,
+    values: Histogram,
+}
+
+impl DoubleHistogram {
+    pub fn from_dynamic_range(highest_to_lowest_value_ratio: i64,
+                              number_of_significant_digits: i32)
+                              -> Result<DoubleHistogram, CreationError> {
+        // validates the number of significant digits, and determines the sub-bucket layout
+        let layout = HistogramConfig::new(2, number_of_significant_digits).build_layout()?;
+        if highest_to_lowest_value_ratio < 2 ||
+           highest_to_lowest_value_ratio as f64 * 10f64.powi(number_of_significant_digits) >= (1i64 << 61) as f64 {
+            return Err(CreationError::HighestToLowestValueRatioOutOfRange(highest_to_lowest_value_ratio));
+        }
+
+        // the internal range needs one binary order of magnitude more than the containing order
+        // of magnitude, as the configured range need not start at a power of two
+        let internal_highest_to_lowest_value_ratio =
+            1i64 << (containing_binary_order_of_magnitude(highest_to_lowest_value_ratio) + 1);
+        // the lower half of the first bucket does not have the required precision, so only the
+        // upper halves of each bucket are used to represent values
+        let lowest_tracking_integer_value = layout.sub_bucket_half_count as i64;
+        let integer_value_range =
+            match lowest_tracking_integer_value.checked_mul(internal_highest_to_lowest_value_ratio) {
+                Some(integer_value_range) => integer_value_range,
+                None => return Err(CreationError::HighestToLowestValueRatioOutOfRange(highest_to_lowest_value_ratio)),
+            };
+        let values = HistogramConfig::new(integer_value_range - 1, number_of_significant_digits).build()?;
+
+        let mut histogram = DoubleHistogram {
+            configured_highest_to_lowest_value_ratio: highest_to_lowest_value_ratio,
+            internal_highest_to_lowest_value_ratio,
+            lowest_tracking_integer_value,
+            current_lowest_value_in_auto_range: 0f64,
+            current_highest_value_limit_in_auto_range: 0f64,
+            double_to_integer_value_conversion_ratio: 0f64,
+            values,
+        };
+        // start far above any realistic value, so that the first recordings shift the range down
+        // to them and leave the upper end of the range free
+        histogram.set_trackable_value_range(2f64.powi(800));
+        Ok(histogram)
+    }
+
+    pub fn record_value(&mut self, value: f64) {
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_expected_interval(&mut self,
+                                               value: f64,
+                                               expected_interval_between_value_samples: f64) {
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&mut self, value: f64) -> Result<(), DoubleRecordError> {
+        self.record_count_at_value(1, value)
+    }
+
+    pub fn try_record_value_with_count(&mut self, value: f64, count: i64) -> Result<(), DoubleRecordError> {
+        self.record_count_at_value(count, value)
+    }
+
+    pub fn try_record_value_with_expected_interval(&mut self,
+                                                   value: f64,
+                                                   expected_interval_between_value_samples: f64)
+                                                   -> Result<(), DoubleRecordError> {
+        record_with_expected_interval(value,
+                                      expected_interval_between_value_samples,
+                                      |value_to_record| self.record_count_at_value(1, value_to_record))
+    }
+
+    pub fn reset(&mut self) {
+        self.values.reset();
+    }
+
+    pub fn get_total_count(&self) -> i64 {
+        self.values.get_total_count()
+    }
+
+    pub fn get_count_at_value(&self, value: f64) -> i64 {
+        self.values.get_count_at_value(self.integer_value(value))
+    }
+
+    pub fn get_count_between_values(&self, lower: f64, upper: f64) -> i64 {
+        self.values.get_count_between_values(self.integer_value(lower), self.integer_value(upper))
+    }
+
+    pub fn get_min_value(&self) -> f64 {
+        self.values.get_scaled_min_value()
+    }
+
+    pub fn get_max_value(&self) -> f64 {
+        self.values.get_scaled_max_value()
+    }
+
+    pub fn get_mean(&self) -> f64 {
+        self.values.get_scaled_mean()
+    }
+
+    pub fn get_std_deviation(&self) -> f64 {
+        self.values.get_scaled_std_deviation()
+    }
+
+    pub fn get_value_at_percentile(&self, percentile: f64) -> f64 {
+        self.values.get_scaled_value_at_percentile(percentile)
+    }
+
+    pub fn get_percentile_at_or_below_value(&self, value: f64) -> f64 {
+        self.values.get_percentile_at_or_below_value(self.integer_value(value))
+    }
+
+    pub fn lowest_equivalent_value(&self, value: f64) -> f64 {
+        self.values.lowest_equivalent_value(self.integer_value(value)) as f64 *
+        self.values.get_integer_to_double_value_conversion_ratio()
+    }
+
+    pub fn highest_equivalent_value(&self, value: f64) -> f64 {
+        let next_non_equivalent_value = (self.values.highest_equivalent_value(self.integer_value(value)) + 1) as f64 *
+                                        self.values.get_integer_to_double_value_conversion_ratio();
+        f64::from_bits(next_non_equivalent_value.to_bits() - 1)
+    }
+
+    pub fn values_are_equivalent(&self, value_one: f64, value_two: f64) -> bool {
+        self.lowest_equivalent_value(value_one) == self.lowest_equivalent_value(value_two)
+    }
+
+    pub fn get_highest_to_lowest_value_ratio(&self) -> i64 {
+        self.configured_highest_to_lowest_value_ratio
+    }
+
+    pub fn get_number_of_significant_value_digits(&self) -> i32 {
+        self.values.get_number_of_significant_value_digits()
+    }
+
+    pub fn get_current_lowest_value_in_auto_range(&self) -> f64 {
+        self.current_lowest_value_in_auto_range
+    }
+
+    pub fn get_current_highest_value_limit_in_auto_range(&self) -> f64 {
+        self.current_highest_value_limit_in_auto_range
+    }
+
+    pub fn get_integer_to_double_value_conversion_ratio(&self) -> f64 {
+        self.values.get_integer_to_double_value_conversion_ratio()
+    }
+
+    pub fn serialise(&self, target_buffer: &mut Vec<u8>) {
+        put_i32(DOUBLE_HISTOGRAM_COOKIE, target_buffer);
+        put_i32(self.get_number_of_significant_value_digits(), target_buffer);
+        put_i64(self.configured_highest_to_lowest_value_ratio, target_buffer);
+        self.values.serialise(target_buffer);
+    }
+
+    fn record_count_at_value(&mut self, count: i64, value: f64) -> Result<(), DoubleRecordError> {
+        if value < 0f64 {
+            return Err(DoubleRecordError::NegativeValue(value));
+        }
+        if count < 0 {
+            return Err(DoubleRecordError::NegativeCount(count));
+        }
+        if value.is_nan() {
+            return Err(DoubleRecordError::ValueOutOfRange(value));
+        }
+        // zero is always covered, without adjusting the range
+        if value != 0f64 &&
+           (value < self.current_lowest_value_in_auto_range ||
+            value >= self.current_highest_value_limit_in_auto_range) {
+            self.auto_adjust_range_for_value(value)?;
+        }
+        match self.values.try_record_value_with_count(self.integer_value(value), count) {
+            Ok(()) => Ok(()),
+            Err(RecordError::CountOverflow) => Err(DoubleRecordError::CountOverflow),
+            Err(_) => Err(DoubleRecordError::ValueOutOfRange(value)),
+        }
+    }
+
+    fn auto_adjust_range_for_value(&mut self, value: f64) -> Result<(), DoubleRecordError> {
+        if value < self.current_lowest_value_in_auto_range {
+            while value < self.current_lowest_value_in_auto_range {
+                let shift = self.capped_containing_binary_order_of_magnitude(
+                    (self.current_lowest_value_in_auto_range / value).ceil() - 1f64);
+                if !self.shift_covered_range_down(shift) {
+                    return Err(DoubleRecordError::ValueOutOfRange(value));
+                }
+            }
+        } else {
+            if value > highest_allowed_value_ever() {
+                return Err(DoubleRecordError::ValueOutOfRange(value));
+            }
+            // a value that is an exact multiple of the limit belongs to the next range up, so
+            // the shift is computed from the next representable value
+            let next_value = f64::from_bits(value.to_bits() + 1);
+            while value >= self.current_highest_value_limit_in_auto_range {
+                let shift = self.capped_containing_binary_order_of_magnitude(
+                    (next_value / self.current_highest_value_limit_in_auto_range).ceil() - 1f64);
+                if !self.shift_covered_range_up(shift) {
+                    return Err(DoubleRecordError::ValueOutOfRange(value));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the covered range down by `shift` binary orders of magnitude, which scales the
+    /// integer representation of recorded values up. Returns false if the values cannot be scaled.
+    fn shift_covered_range_down(&mut self, shift: i32) -> bool {
+        if self.values.shift_values_left(shift as u32).is_err() {
+            return false;
+        }
+        let lowest_value_in_auto_range = self.current_lowest_value_in_auto_range / (1i64 << shift) as f64;
+        self.set_trackable_value_range(lowest_value_in_auto_range);
+        true
+    }
+
+    /// Moves the covered range up by `shift` binary orders of magnitude, which scales the integer
+    /// representation of recorded values down. Returns false if that would lose precision.
+    fn shift_covered_range_up(&mut self, shift: i32) -> bool {
+        if self.values.shift_values_right(shift as u32).is_err() {
+            return false;
+        }
+        let lowest_value_in_auto_range = self.current_lowest_value_in_auto_range * (1i64 << shift) as f64;
+        self.set_trackable_value_range(lowest_value_in_auto_range);
+        true
+    }
+
+    fn set_trackable_value_range(&mut self, lowest_value_in_auto_range: f64) {
+        self.current_lowest_value_in_auto_range = lowest_value_in_auto_range;
+        self.current_highest_value_limit_in_auto_range =
+            lowest_value_in_auto_range * self.internal_highest_to_lowest_value_ratio as f64;
+        let integer_to_double_value_conversion_ratio = lowest_value_in_auto_range / self.lowest_tracking_integer_value as f64;
+        self.values.set_integer_to_double_value_conversion_ratio(integer_to_double_value_conversion_ratio);
+        self.double_to_integer_value_conversion_ratio = 1f64 / integer_to_double_value_conversion_ratio;
+    }
+
+    fn capped_containing_binary_order_of_magnitude(&self, value: f64) -> i32 {
+        if value > self.configured_highest_to_lowest_value_ratio as f64 {
+            (self.configured_highest_to_lowest_value_ratio as f64).log2() as i32
+        } else if value > 2f64.powi(50) {
+            50
+        } else {
+            containing_binary_order_of_magnitude(value.ceil() as i64)
+        }
+    }
+
+    fn integer_value(&self, value: f64) -> i64 {
+        (value * self.double_to_integer_value_conversion_ratio) as i64
+    }
+}
+
+fn containing_binary_order_of_magnitude(value: i64) -> i32 {
+    64 - value.leading_zeros() as i32
+}
+
+/// The range is not allowed to move so high that a value multiplied by the internal ratio could
+/// become infinite.
+fn highest_allowed_value_ever() -> f64 {
+    let mut value = 1f64;
+    while value < f64::MAX / 4f64 {
+        value *= 2f64;
+    }
+    value
+}
@@ -5,7 +5,7 @@ pub fn put_i32(input_value: i32, buffer: &mut Vec<u8>) {
 	buffer.push((input_value) as u8);
 }
 
-pub fn put_i32_at_offset(input_value: i32, buffer: &mut Vec<u8>, offset: i32) {
+pub fn put_i32_at_offset(input_value: i32, buffer: &mut [u8], offset: i32) {
 	let offset = offset as usize;
 	buffer[offset] = (input_value >> 24) as u8;
 	buffer[offset + 1] = (input_value >> 16) as u8;
@@ -13,7 +13,7 @@ pub fn put_i32_at_offset(input_value: i32, buffer: &mut Vec<u8>, offset: i32) {
 	buffer[offset + 3] = input_value as u8;
 }
 
-pub fn get_i32(buffer: &Vec<u8>, offset: i32) -> i32 {
+pub fn get_i32(buffer: &[u8], offset: i32) -> i32 {
 	let offset = offset as usize;
 	let i0 = (buffer[offset] as i32) << 24;
 	let i1 = (buffer[offset + 1] as i32) << 16;
@@ -34,7 +34,7 @@ pub fn put_i64(input_value: i64, buffer: &mut Vec<u8>) {
 	buffer.push(input_value as u8);
 }
 
-pub fn get_i64(buffer: &Vec<u8>, offset: i32) -> i64 {
+pub fn get_i64(buffer: &[u8], offset: i32) -> i64 {
 	let offset = offset as usize;
 	let i0 = (buffer[offset] as i64) << 56;
 	let i1 = (buffer[offset + 1] as i64) << 48;
@@ -45,12 +45,11 @@ pub fn get_i64(buffer: &Vec<u8>, offset: i32) -> i64 {
 	let i6 = (buffer[offset + 6] as i64) << 8;
 	let i7 = buffer[offset + 7] as i64;
 	
-	(i0 | i1 | i2 | i3 | i4 | i5 | i6 | i7) as i64
+	i0 | i1 | i2 | i3 | i4 | i5 | i6 | i7
 }
 
 fn zero_test(input_value: i64, shift: isize) -> bool {
-	let result = sign_preserving_shift(input_value, shift) == 0;
-	result
+	sign_preserving_shift(input_value, shift) == 0
 }
 
 fn sign_preserving_shift(input_value: i64, shift: isize) -> i64 {
@@ -102,42 +101,42 @@ pub fn encode(input_value: i64, buffer: &mut Vec<u8>) {
     }
 }
 
-pub fn decode(buffer: &Vec<u8>, input_offset: i32) -> (i64, i32) {
+pub fn decode(buffer: &[u8], input_offset: i32) -> (i64, i32) {
     let offset = input_offset as usize;
     let mut v: i64 = buffer[offset] as i64;
-    let mut value: i64 = (v & 0x7F) as i64;
+    let mut value: i64 = v & 0x7F;
     let mut consumed_bytes: i32 = 1;
     if (v & 0x80) != 0 {
         v = buffer[offset + 1] as i64;
-        value |= ((v & 0x7F) << 7) as i64;
+        value |= (v & 0x7F) << 7;
         consumed_bytes = 2;
         if (v & 0x80) != 0 {
             v = buffer[offset + 2] as i64;
-            value |= ((v & 0x7F) << 14) as i64;
+            value |= (v & 0x7F) << 14;
             consumed_bytes = 3;
             if (v & 0x80) != 0 {
                 v = buffer[offset + 3] as i64;
-                value |= ((v & 0x7F) << 21) as i64;
+                value |= (v & 0x7F) << 21;
                 consumed_bytes = 4;
                 if (v & 0x80) != 0 {
                     v = buffer[offset + 4] as i64;
-                    value |= ((v & 0x7F) << 28) as i64;
+                    value |= (v & 0x7F) << 28;
                     consumed_bytes = 5;
                     if (v & 0x80) != 0 {
                         v = buffer[offset + 5] as i64;
-                        value |= ((v & 0x7F) << 35) as i64;
+                        value |= (v & 0x7F) << 35;
                         consumed_bytes = 6;
                         if (v & 0x80) != 0 {
                             v = buffer[offset + 6] as i64;
-                            value |= ((v & 0x7F) << 42) as i64;
+                            value |= (v & 0x7F) << 42;
                             consumed_bytes = 7;
                             if (v & 0x80) != 0 {
                                 v = buffer[offset + 7] as i64;
-                                value |= ((v & 0x7F) << 49) as i64;
+                                value |= (v & 0x7F) << 49;
                                 consumed_bytes = 8;
                                 if (v & 0x80) != 0 {
                                     v = buffer[offset + 8] as i64;
-                                    value |= (v << 56) as i64;
+                                    value |= v << 56;
                                     consumed_bytes = 9;
                                 }
                             }
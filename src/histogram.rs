@@ -1,11 +1,15 @@
 use std::cmp;
+use std::error;
 use std::fmt;
-use std;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Sub;
+use counter::*;
 use iter::*;
 use encoding::*;
 
-const I32_BYTES: i32 = 4;
-const I64_BYTES: i32 = 8;
+pub(crate) const I32_BYTES: i32 = 4;
+pub(crate) const I64_BYTES: i32 = 8;
 
 pub fn new_histogram(_highest_trackable_value: i64,
                      _number_of_significant_digits: i32)
@@ -17,138 +21,438 @@ pub fn new_histogram_lower_bound(_lowest_discernible_value: i64,
                                  _highest_trackable_value: i64,
                                  _number_of_significant_digits: i32)
                                  -> Histogram {
-    let largest_value_with_single_unit_resolution = 2 *
-                                                    10i64.pow(_number_of_significant_digits as u32);
-    let _unit_magnitude = ((_lowest_discernible_value as f64).ln() / 2f64.ln()) as i32;
-    let sub_bucket_count_magnitude = ((largest_value_with_single_unit_resolution as f64).ln() /
-                                      2f64.ln())
-                                         .ceil() as i32;
-    let _sub_bucket_half_count_magnitude = if sub_bucket_count_magnitude > 1 {
-        sub_bucket_count_magnitude - 1
-    } else {
-        0
-    };
-    let _sub_bucket_count = 2i64.pow((_sub_bucket_half_count_magnitude + 1) as u32) as i32;
-    let _sub_bucket_half_count = _sub_bucket_count / 2;
-    let _sub_bucket_mask = ((_sub_bucket_count as i64) - 1) << _unit_magnitude;
-
-    let _leading_zero_count_base = 64 - _unit_magnitude - _sub_bucket_half_count_magnitude - 1;
-
-    // establish size (highest_trackable_value)
-    let _counts_array_length = determine_array_length_needed(_highest_trackable_value,
-                                                             _sub_bucket_count,
-                                                             _unit_magnitude);
-    let _bucket_count = get_buckets_needed_to_cover_value(_highest_trackable_value,
-                                                          _sub_bucket_count,
-                                                          _unit_magnitude);
-
-    Histogram {
-        values: vec![0; _counts_array_length as usize].into_boxed_slice(),
-        total_count: 0,
-        highest_trackable_value: _highest_trackable_value,
-        lowest_discernible_value: _lowest_discernible_value,
-        number_of_significant_digits: _number_of_significant_digits,
-        bucket_count: _bucket_count,
-        sub_bucket_count: _sub_bucket_count,
-        counts_array_length: _counts_array_length,
-        word_size_in_bytes: 8,
-        unit_magnitude: _unit_magnitude,
-        sub_bucket_half_count_magnitude: _sub_bucket_half_count_magnitude,
-        sub_bucket_half_count: _sub_bucket_half_count,
-        leading_zero_count_base: _leading_zero_count_base,
-        sub_bucket_mask: _sub_bucket_mask,
-        max_value: 0,
-        min_non_zero_value: std::i64::MAX,
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits)
+        .lowest_discernible_value(_lowest_discernible_value);
+    match config.build() {
+        Ok(histogram) => histogram,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreationError {
+    /// The lowest discernible value must be at least 1
+    LowestDiscernibleValueTooSmall(i64),
+    /// The lowest discernible value must be no more than half of `i64::MAX`
+    LowestDiscernibleValueTooLarge(i64),
+    /// The highest trackable value must be at least twice the lowest discernible value
+    HighestTrackableValueTooSmall(i64),
+    /// The number of significant digits must be between 0 and 5 inclusive
+    SignificantDigitsOutOfRange(i32),
+    /// The requested precision cannot be represented above the lowest discernible value
+    CannotRepresentSignificantDigitsBeyondLowest,
+    /// The histogram implementation cannot resize its counts array
+    AutoResizeNotSupported,
+    /// The highest to lowest value ratio of a `DoubleHistogram` must be at least 2, and small
+    /// enough to be tracked at the requested precision
+    HighestToLowestValueRatioOutOfRange(i64),
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CreationError::LowestDiscernibleValueTooSmall(value) => {
+                write!(f, "lowest discernible value {} must be >= 1", value)
+            }
+            CreationError::LowestDiscernibleValueTooLarge(value) => {
+                write!(f, "lowest discernible value {} must be <= i64::MAX / 2", value)
+            }
+            CreationError::HighestTrackableValueTooSmall(value) => {
+                write!(f,
+                       "highest trackable value {} must be >= 2 * lowest discernible value",
+                       value)
+            }
+            CreationError::SignificantDigitsOutOfRange(digits) => {
+                write!(f, "number of significant digits {} must be between 0 and 5", digits)
+            }
+            CreationError::CannotRepresentSignificantDigitsBeyondLowest => {
+                write!(f,
+                       "cannot represent the number of significant digits worth of values \
+                        beyond the lowest discernible value")
+            }
+            CreationError::AutoResizeNotSupported => {
+                write!(f, "auto-resize is not supported by this histogram implementation")
+            }
+            CreationError::HighestToLowestValueRatioOutOfRange(ratio) => {
+                write!(f,
+                       "highest to lowest value ratio {} must be >= 2, and small enough for the \
+                        values it spans to be tracked at the number of significant digits",
+                       ratio)
+            }
+        }
+    }
+}
+
+impl error::Error for CreationError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistogramConfig {
+    pub(crate) lowest_discernible_value: i64,
+    pub(crate) highest_trackable_value: i64,
+    pub(crate) number_of_significant_digits: i32,
+    pub(crate) auto_resize: bool,
+}
+
+impl HistogramConfig {
+    pub fn new(highest_trackable_value: i64, number_of_significant_digits: i32) -> HistogramConfig {
+        HistogramConfig {
+            lowest_discernible_value: 1,
+            highest_trackable_value,
+            number_of_significant_digits,
+            auto_resize: false,
+        }
+    }
+
+    pub fn lowest_discernible_value(mut self, lowest_discernible_value: i64) -> HistogramConfig {
+        self.lowest_discernible_value = lowest_discernible_value;
+        self
+    }
+
+    pub fn auto_resize(mut self, auto_resize: bool) -> HistogramConfig {
+        self.auto_resize = auto_resize;
+        self
+    }
+
+    pub fn build(&self) -> Result<Histogram, CreationError> {
+        self.build_with_counter()
+    }
+
+    pub fn build_with_counter<T: Counter>(&self) -> Result<Histogram<T>, CreationError> {
+        self.build_with_storage()
+    }
+
+    pub fn build_with_storage<T: Counter, S: CountsArray<T>>(&self) -> Result<Histogram<T, S>, CreationError> {
+        let layout = self.build_layout()?;
+        let counts_array_length = layout.counts_array_length_needed(self.highest_trackable_value);
+
+        Ok(Histogram {
+            values: S::with_length(counts_array_length as usize),
+            counter: PhantomData,
+            total_count: 0,
+            highest_trackable_value: self.highest_trackable_value,
+            lowest_discernible_value: self.lowest_discernible_value,
+            number_of_significant_digits: self.number_of_significant_digits,
+            bucket_count: layout.buckets_needed(self.highest_trackable_value),
+            counts_array_length,
+            word_size_in_bytes: mem::size_of::<T>() as i32,
+            layout,
+            max_value: 0,
+            min_non_zero_value: i64::MAX,
+            auto_resize: self.auto_resize,
+            start_time: 0,
+            end_time: 0,
+            tag: None,
+            integer_to_double_value_conversion_ratio: 1f64,
+            normalizing_index_offset: 0,
+        })
     }
 
+    pub(crate) fn build_layout(&self) -> Result<BucketLayout, CreationError> {
+        if self.lowest_discernible_value < 1 {
+            return Err(CreationError::LowestDiscernibleValueTooSmall(self.lowest_discernible_value));
+        }
+        if self.number_of_significant_digits < 0 || self.number_of_significant_digits > 5 {
+            return Err(CreationError::SignificantDigitsOutOfRange(self.number_of_significant_digits));
+        }
+        if self.lowest_discernible_value > i64::MAX / 2 {
+            return Err(CreationError::LowestDiscernibleValueTooLarge(self.lowest_discernible_value));
+        }
+        if self.highest_trackable_value < 2 * self.lowest_discernible_value {
+            return Err(CreationError::HighestTrackableValueTooSmall(self.highest_trackable_value));
+        }
+
+        let largest_value_with_single_unit_resolution =
+            2 * 10i64.pow(self.number_of_significant_digits as u32);
+        let unit_magnitude = ((self.lowest_discernible_value as f64).ln() / 2f64.ln()) as i32;
+        let sub_bucket_count_magnitude = ((largest_value_with_single_unit_resolution as f64).ln() /
+                                          2f64.ln())
+                                             .ceil() as i32;
+        let sub_bucket_half_count_magnitude = if sub_bucket_count_magnitude > 1 {
+            sub_bucket_count_magnitude - 1
+        } else {
+            0
+        };
+        if unit_magnitude + sub_bucket_half_count_magnitude > 61 {
+            return Err(CreationError::CannotRepresentSignificantDigitsBeyondLowest);
+        }
+        let sub_bucket_count = 2i64.pow((sub_bucket_half_count_magnitude + 1) as u32) as i32;
+
+        Ok(BucketLayout {
+            unit_magnitude,
+            sub_bucket_count,
+            sub_bucket_half_count_magnitude,
+            sub_bucket_half_count: sub_bucket_count / 2,
+            leading_zero_count_base: 64 - unit_magnitude - sub_bucket_half_count_magnitude - 1,
+            sub_bucket_mask: ((sub_bucket_count as i64) - 1) << unit_magnitude,
+        })
+    }
+}
+
+pub fn deserialise_histogram(byte_array: &[u8], offset: i32) -> Option<Histogram> {
+    deserialise_histogram_with_counter(byte_array, offset)
+}
+
+pub fn deserialise_histogram_with_counter<T: Counter>(byte_array: &[u8], offset: i32) -> Option<Histogram<T>> {
+    deserialise_histogram_with_storage(byte_array, offset)
 }
 
-pub fn deserialise_histogram(byte_array: &Vec<u8>, offset: i32) -> Option<Histogram> {
+pub fn deserialise_histogram_with_storage<T: Counter, S: CountsArray<T>>(byte_array: &[u8],
+                                                                          offset: i32)
+                                                                          -> Option<Histogram<T, S>> {
 	let cookie = get_i32(byte_array, offset);
 	if cookie != (0x1c849303i32 | 0x10i32) {
 		return None;
 	}
 	let payload_length_in_bytes = get_i32(byte_array, offset + I32_BYTES);
-	// TODO assert that normalising_index_offset is zero - any other value is unsupported
-//	let normalising_index_offset = get_i32(byte_array, offset + 2 * I32_BYTES);
+	let normalising_index_offset = get_i32(byte_array, offset + 2 * I32_BYTES);
 	let number_of_significant_digits = get_i32(byte_array, offset + 3 * I32_BYTES);
 	let lowest_trackable_unit_value = get_i64(byte_array, offset + 4 * I32_BYTES);
 	let highest_trackable_value = get_i64(byte_array, offset + (4 * I32_BYTES) + I64_BYTES);
-//	let placeholder = get_i64(byte_array, offset + (4 * I32_BYTES) + (2 * I64_BYTES));
+	let integer_to_double_value_conversion_ratio =
+	    f64::from_bits(get_i64(byte_array, offset + (4 * I32_BYTES) + (2 * I64_BYTES)) as u64);
 	
-	let mut histogram = new_histogram_lower_bound(lowest_trackable_unit_value, highest_trackable_value, number_of_significant_digits);
-	let filled_length = histogram.fill_counts_array_from_source_buffer(byte_array, (4 * I32_BYTES) + (3 * I64_BYTES), payload_length_in_bytes, I64_BYTES);
+	let config = HistogramConfig::new(highest_trackable_value, number_of_significant_digits)
+	    .lowest_discernible_value(lowest_trackable_unit_value);
+	let mut histogram = match config.build_with_storage() {
+		Ok(histogram) => histogram,
+		Err(_) => return None,
+	};
+	// older serialisations wrote 0 here, in which case values are left unscaled
+	if integer_to_double_value_conversion_ratio > 0f64 && integer_to_double_value_conversion_ratio.is_finite() {
+		histogram.integer_to_double_value_conversion_ratio = integer_to_double_value_conversion_ratio;
+	}
+	histogram.normalizing_index_offset = normalising_index_offset.rem_euclid(histogram.counts_array_length);
+	let filled_length = histogram.fill_counts_array_from_source_buffer(byte_array, offset + (4 * I32_BYTES) + (3 * I64_BYTES), payload_length_in_bytes, I64_BYTES)?;
 	histogram.establish_internal_tracking_values(filled_length);
 	
 	Some(histogram)
 }
 
 
-pub struct Histogram {
-    values: Box<[i64]>,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordError {
+    /// The value is beyond the range the histogram covers, and the histogram cannot grow to cover it
+    ValueOutOfRange(i64),
+    /// Negative values cannot be recorded
+    NegativeValue(i64),
+    /// Negative counts cannot be recorded
+    NegativeCount(i64),
+    /// Recording the count would overflow the count at the value's index, or the total count
+    CountOverflow,
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecordError::ValueOutOfRange(value) => {
+                write!(f, "value {} is outside of the histogram's covered range", value)
+            }
+            RecordError::NegativeValue(value) => write!(f, "cannot record negative value {}", value),
+            RecordError::NegativeCount(count) => write!(f, "cannot record negative count {}", count),
+            RecordError::CountOverflow => write!(f, "recording would overflow the histogram's counts"),
+        }
+    }
+}
+
+impl error::Error for RecordError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtractionError {
+    /// The subtracted histogram holds values beyond the range of this histogram
+    ValueOutOfRange(i64),
+    /// Subtracting would leave a negative count at the value
+    NegativeCount(i64),
+}
+
+impl fmt::Display for SubtractionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SubtractionError::ValueOutOfRange(value) => {
+                write!(f, "value {} is outside of the histogram's covered range", value)
+            }
+            SubtractionError::NegativeCount(value) => {
+                write!(f, "subtraction would leave a negative count at value {}", value)
+            }
+        }
+    }
+}
+
+impl error::Error for SubtractionError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftError {
+    /// Shifting would move recorded values beyond the highest trackable value
+    Overflow,
+    /// Shifting would move recorded values into the lowest half-bucket, losing precision
+    Underflow,
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShiftError::Overflow => write!(f, "shift would overflow the histogram's covered range"),
+            ShiftError::Underflow => write!(f, "shift would lose precision of recorded values"),
+        }
+    }
+}
+
+impl error::Error for ShiftError {}
+
+pub struct Histogram<T: Counter = u64, S: CountsArray<T> = Vec<T>> {
+    values: S,
+    counter: PhantomData<T>,
     total_count: i64,
     highest_trackable_value: i64,
     lowest_discernible_value: i64,
     number_of_significant_digits: i32,
     bucket_count: i32,
-    sub_bucket_count: i32,
     counts_array_length: i32,
     word_size_in_bytes: i32,
-    unit_magnitude: i32,
-    sub_bucket_half_count_magnitude: i32,
-    sub_bucket_half_count: i32,
-    leading_zero_count_base: i32,
-    sub_bucket_mask: i64,
+    layout: BucketLayout,
     max_value: i64,
     min_non_zero_value: i64,
+    auto_resize: bool,
+    start_time: i64,
+    end_time: i64,
+    tag: Option<String>,
+    integer_to_double_value_conversion_ratio: f64,
+    normalizing_index_offset: i32,
 }
 
-impl Histogram {
+impl<T: Counter, S: CountsArray<T>> Histogram<T, S> {
     pub fn get_count_at_index(&self, index: i32) -> i64 {
-        self.values[index as usize]
+        self.values.get(self.normalize_index(index)).as_i64()
     }
 
     pub fn value_from_index(&self, index: i32) -> i64 {
-        let mut bucket_index: i32 = (index >> self.sub_bucket_half_count_magnitude) - 1;
-        let mut sub_bucket_index: i32 = (index & (self.sub_bucket_half_count - 1)) +
-                                        self.sub_bucket_half_count;
-        if bucket_index < 0 {
-            sub_bucket_index -= self.sub_bucket_half_count;
-            bucket_index = 0;
-        }
-        self.value_from_index_by_bucket(bucket_index, sub_bucket_index)
+        self.layout.value_from_index(index)
     }
 
     pub fn lowest_equivalent_value(&self, value: i64) -> i64 {
-        let bucket_index = self.get_bucket_index(value);
-        let sub_bucket_index = self.get_sub_bucket_index(value, bucket_index);
-        self.value_from_index_by_bucket(bucket_index, sub_bucket_index)
+        self.layout.lowest_equivalent_value(value)
     }
 
     pub fn highest_equivalent_value(&self, value: i64) -> i64 {
-        self.next_non_equivalent_value(value) - 1
+        self.layout.highest_equivalent_value(value)
     }
 
     pub fn record_value_with_expected_interval(&mut self,
                                                value: i64,
                                                expected_interval_between_value_samples: i64) {
-        if expected_interval_between_value_samples <= 0 {
-            return;
-        }
-        self.record_value(value);
-        let mut missing_value = value - expected_interval_between_value_samples;
-        while missing_value >= expected_interval_between_value_samples {
-            self.record_single_value(missing_value);
-            missing_value -= expected_interval_between_value_samples;
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
         }
     }
 
     pub fn record_value(&mut self, value: i64) {
-        self.record_single_value(value);
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_count(&mut self, value: i64, count: i64) {
+        if let Err(e) = self.try_record_value_with_count(value, count) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_count_and_expected_interval(&mut self,
+                                                         value: i64,
+                                                         count: i64,
+                                                         expected_interval_between_value_samples: i64) {
+        if let Err(e) = self.try_record_value_with_count_and_expected_interval(value,
+                                                                               count,
+                                                                               expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_values(&mut self, values: &[i64]) {
+        if let Err(e) = self.try_record_values(values) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&mut self, value: i64) -> Result<(), RecordError> {
+        self.record_single_value(value)
+    }
+
+    pub fn try_record_value_with_count(&mut self, value: i64, count: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(count, value)
+    }
+
+    /// Records `count` occurrences of `value`, then back-fills `count` occurrences of each missing
+    /// value in the same way as `try_record_value_with_expected_interval`.
+    pub fn try_record_value_with_count_and_expected_interval(&mut self,
+                                                             value: i64,
+                                                             count: i64,
+                                                             expected_interval_between_value_samples: i64)
+                                                             -> Result<(), RecordError> {
+        self.record_count_at_value_with_expected_interval(count, value, expected_interval_between_value_samples)
+    }
+
+    /// Records each of `values` once, updating the total count and min/max once for the whole
+    /// slice. Nothing is recorded if any of the values cannot be.
+    pub fn try_record_values(&mut self, values: &[i64]) -> Result<(), RecordError> {
+        let mut max_value = 0;
+        let mut min_non_zero_value = i64::MAX;
+        for &value in values {
+            if value < 0 {
+                return Err(RecordError::NegativeValue(value));
+            }
+            max_value = cmp::max(max_value, value);
+            if value != 0 {
+                min_non_zero_value = cmp::min(min_non_zero_value, value);
+            }
+        }
+        if self.counts_array_index(max_value) >= self.counts_array_length {
+            if !self.auto_resize {
+                return Err(RecordError::ValueOutOfRange(max_value));
+            }
+            self.try_resize(max_value)?;
+        }
+        let new_total_count = match self.total_count.checked_add(values.len() as i64) {
+            Some(total_count) => total_count,
+            None => return Err(RecordError::CountOverflow),
+        };
+
+        for (recorded, &value) in values.iter().enumerate() {
+            let counts_index = self.counts_array_index(value);
+            let new_count = self.get_count_at_index(counts_index).checked_add(1).and_then(T::from_i64);
+            match new_count {
+                Some(new_count) => self.values.set(self.normalize_index(counts_index), new_count),
+                None => {
+                    // undo the values recorded so far
+                    for &recorded_value in &values[..recorded] {
+                        let recorded_index = self.counts_array_index(recorded_value);
+                        let count = self.get_count_at_index(recorded_index);
+                        self.set_count_at_index(recorded_index, count - 1);
+                    }
+                    return Err(RecordError::CountOverflow);
+                }
+            }
+        }
+        self.total_count = new_total_count;
+        self.update_min_and_max(max_value);
+        if min_non_zero_value != i64::MAX {
+            self.update_min_and_max(min_non_zero_value);
+        }
+        Ok(())
+    }
+
+    /// Records `value`, then back-fills the values that would have been recorded had samples been
+    /// taken every `expected_interval_between_value_samples`. With a non-positive expected
+    /// interval, only `value` itself is recorded.
+    pub fn try_record_value_with_expected_interval(&mut self,
+                                                   value: i64,
+                                                   expected_interval_between_value_samples: i64)
+                                                   -> Result<(), RecordError> {
+        self.record_count_at_value_with_expected_interval(1, value, expected_interval_between_value_samples)
     }
 
     pub fn get_min_value(&self) -> i64 {
-        if self.min_non_zero_value == std::i64::MAX {
+        if self.min_non_zero_value == i64::MAX {
             0
         } else {
             self.min_non_zero_value
@@ -164,19 +468,15 @@ impl Histogram {
             return 0f64;
         }
 
-        let mut iter = new_iterator(self);
-
-        iter.reset(self.total_count, self.unit_magnitude);
         let mut total_value = 0.0f64;
 
-        while iter.has_next() {
-            let iteration_value = iter.next();
+        for iteration_value in self.iter_recorded() {
             total_value +=
                 (self.median_equivalent_value(iteration_value.get_value_iterated_to()) *
                  iteration_value.get_count_at_value_iterated_to()) as f64;
         }
 
-        return total_value / self.total_count as f64;
+        total_value / self.total_count as f64
     }
 
     pub fn get_std_deviation(&self) -> f64 {
@@ -187,11 +487,7 @@ impl Histogram {
         let mean = self.get_mean();
         let mut geometric_deviation_total = 0f64;
 
-        let mut iter = new_iterator(self);
-
-        iter.reset(self.total_count, self.unit_magnitude);
-        while iter.has_next() {
-            let iteration_value = iter.next();
+        for iteration_value in self.iter_recorded() {
             let deviation =
                 self.median_equivalent_value(iteration_value.get_value_iterated_to()) as f64 - mean;
             geometric_deviation_total +=
@@ -218,35 +514,266 @@ impl Histogram {
         (100 * total_to_current_index) as f64 / self.total_count as f64
     }
 
+    /// Returns the percentile at or below each of the given values, in the order requested, as
+    /// `get_percentile_at_or_below_value` would, but with a single pass over the counts array.
+    pub fn get_percentiles_at_values(&self, values: &[i64]) -> Vec<f64> {
+        if self.total_count == 0 {
+            return vec![100f64; values.len()];
+        }
+        let target_indexes: Vec<i32> = values.iter()
+            .map(|&value| cmp::min(self.counts_array_index(value), self.counts_array_length - 1))
+            .collect();
+        let mut requests: Vec<usize> = (0..values.len()).collect();
+        requests.sort_by_key(|&request| target_indexes[request]);
+
+        let mut percentiles = vec![0f64; values.len()];
+        let mut total_to_current_index = 0i64;
+        let mut next_index = 0;
+        for request in requests {
+            while next_index <= target_indexes[request] {
+                total_to_current_index += self.get_count_at_index(next_index);
+                next_index += 1;
+            }
+            percentiles[request] = (100 * total_to_current_index) as f64 / self.total_count as f64;
+        }
+
+        percentiles
+    }
+
     pub fn get_count_between_values(&self, lower: i64, upper: i64) -> i64 {
         let low_index = cmp::max(0, self.counts_array_index(lower));
         let high_index = cmp::min(self.counts_array_index(upper), self.counts_array_length - 1);
         let mut count = 0i64;
         for i in low_index..(high_index + 1) {
-            count += self.get_count_at_index(i as i32);
+            count += self.get_count_at_index(i);
         }
         count
     }
 
     pub fn reset(&mut self) {
         self.total_count = 0;
-        for i in 0..self.counts_array_length {
-            self.values[i as usize] = 0;
+        self.values.clear();
+        self.normalizing_index_offset = 0;
+        self.max_value = 0;
+        self.min_non_zero_value = i64::MAX;
+        self.start_time = 0;
+        self.end_time = 0;
+        self.tag = None;
+    }
+
+    /// Adds the values recorded in `other` to this histogram.
+    ///
+    /// If both histograms share the same bucket layout the counts arrays are summed; otherwise
+    /// each of `other`'s populated buckets is recorded at its median equivalent value. Nothing is
+    /// added if `other` holds values beyond this histogram's range (and this histogram does not
+    /// auto-resize), or if adding would overflow a count.
+    ///
+    /// Once added, this histogram covers the period from the earlier of the two start times to the
+    /// later of the two end times.
+    pub fn add<U: Counter, R: CountsArray<U>>(&mut self, other: &Histogram<U, R>) -> Result<(), RecordError> {
+        if other.total_count == 0 {
+            self.merge_time_range(other);
+            return Ok(());
+        }
+        let other_max_value = other.highest_equivalent_value(other.max_value);
+        if self.counts_array_index(other_max_value) >= self.counts_array_length {
+            if !self.auto_resize {
+                return Err(RecordError::ValueOutOfRange(other.max_value));
+            }
+            self.try_resize(other_max_value).map_err(|_| RecordError::ValueOutOfRange(other.max_value))?;
+        }
+
+        let additions = self.counts_by_index_of(other);
+        let recorded_values = if self.layout == other.layout {
+            (other.get_min_value(), other.max_value)
+        } else {
+            (other.median_equivalent_value(other.get_min_value()), other.median_equivalent_value(other.max_value))
+        };
+
+        let new_total_count = match self.total_count.checked_add(other.total_count) {
+            Some(total_count) => total_count,
+            None => return Err(RecordError::CountOverflow),
+        };
+        let mut new_counts = Vec::with_capacity(additions.len());
+        for &(counts_index, count) in &additions {
+            match self.get_count_at_index(counts_index).checked_add(count).and_then(T::from_i64) {
+                Some(new_count) => new_counts.push((counts_index, new_count)),
+                None => return Err(RecordError::CountOverflow),
+            }
+        }
+        for (counts_index, new_count) in new_counts {
+            self.values.set(self.normalize_index(counts_index), new_count);
+        }
+        self.total_count = new_total_count;
+        self.update_min_and_max(recorded_values.0);
+        self.update_min_and_max(recorded_values.1);
+        self.merge_time_range(other);
+        Ok(())
+    }
+
+    /// Subtracts the values recorded in `other` from this histogram.
+    ///
+    /// Buckets are matched in the same way as by `add`. Nothing is subtracted if `other` holds
+    /// values beyond this histogram's range, or if any count would become negative.
+    pub fn subtract<U: Counter, R: CountsArray<U>>(&mut self,
+                                                   other: &Histogram<U, R>)
+                                                   -> Result<(), SubtractionError> {
+        if other.total_count == 0 {
+            return Ok(());
+        }
+        if self.counts_array_index(other.highest_equivalent_value(other.max_value)) >= self.counts_array_length {
+            return Err(SubtractionError::ValueOutOfRange(other.max_value));
+        }
+
+        let subtractions = self.counts_by_index_of(other);
+        let mut new_counts = Vec::with_capacity(subtractions.len());
+        for &(counts_index, count) in &subtractions {
+            let current_count = self.get_count_at_index(counts_index);
+            if current_count < count {
+                return Err(SubtractionError::NegativeCount(self.value_from_index(counts_index)));
+            }
+            new_counts.push((counts_index, current_count - count));
+        }
+        for (counts_index, new_count) in new_counts {
+            self.set_count_at_index(counts_index, new_count);
+        }
+        let counts_array_length = self.counts_array_length;
+        self.establish_internal_tracking_values(counts_array_length);
+        Ok(())
+    }
+
+    /// Makes `target` an exact copy of this histogram, reusing `target`'s counts array storage.
+    pub fn copy_into(&self, target: &mut Histogram<T, S>) {
+        target.values.clear();
+        target.values.resize(self.counts_array_length as usize);
+        for index in 0..self.counts_array_length as usize {
+            let count = self.values.get(index);
+            if count != T::zero() {
+                target.values.set(index, count);
+            }
+        }
+        target.total_count = self.total_count;
+        target.highest_trackable_value = self.highest_trackable_value;
+        target.lowest_discernible_value = self.lowest_discernible_value;
+        target.number_of_significant_digits = self.number_of_significant_digits;
+        target.bucket_count = self.bucket_count;
+        target.counts_array_length = self.counts_array_length;
+        target.word_size_in_bytes = self.word_size_in_bytes;
+        target.layout = self.layout;
+        target.max_value = self.max_value;
+        target.min_non_zero_value = self.min_non_zero_value;
+        target.auto_resize = self.auto_resize;
+        target.start_time = self.start_time;
+        target.end_time = self.end_time;
+        target.tag.clone_from(&self.tag);
+        target.integer_to_double_value_conversion_ratio = self.integer_to_double_value_conversion_ratio;
+        target.normalizing_index_offset = self.normalizing_index_offset;
+    }
+
+    /// Returns a copy of this histogram, corrected for coordinated omission as if each recorded
+    /// value had been recorded with `record_value_with_expected_interval`.
+    pub fn copy_corrected_for_coordinated_omission(&self, expected_interval_between_value_samples: i64) -> Histogram<T, S> {
+        let mut corrected = self.new_empty_copy();
+        if let Err(e) = corrected.add_while_correcting_for_coordinated_omission(self,
+                                                                                 expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+        corrected
+    }
+
+    /// Adds the values recorded in `other` to this histogram, correcting them for coordinated
+    /// omission as if each had been recorded with `record_value_with_expected_interval`. Nothing
+    /// is added if any corrected value cannot be recorded.
+    pub fn add_while_correcting_for_coordinated_omission<U: Counter, R: CountsArray<U>>(&mut self,
+                                                                                         other: &Histogram<U, R>,
+                                                                                         expected_interval_between_value_samples: i64)
+                                                                                         -> Result<(), RecordError> {
+        let mut corrected = self.new_empty_copy();
+        for iteration_value in other.iter_recorded() {
+            corrected.record_count_at_value_with_expected_interval(iteration_value.get_count_at_value_iterated_to(),
+                                                                   iteration_value.get_value_iterated_to(),
+                                                                   expected_interval_between_value_samples)?;
+        }
+        self.add(&corrected)
+    }
+
+    /// Multiplies all recorded values by `2^number_of_binary_orders_of_magnitude`.
+    ///
+    /// Values above the lowest half-bucket are shifted by moving the normalizing index offset, so
+    /// the shift does not depend on the size of the counts array. Values in the lowest half-bucket
+    /// are moved to the indexes of their shifted values, so the shift is exact.
+    pub fn shift_values_left(&mut self, number_of_binary_orders_of_magnitude: u32) -> Result<(), ShiftError> {
+        let shift = number_of_binary_orders_of_magnitude;
+        if shift == 0 || self.total_count == self.get_count_at_index(0) {
+            return Ok(());
+        }
+        if shift >= 63 || self.max_value > self.highest_trackable_value >> shift {
+            return Err(ShiftError::Overflow);
         }
+
+        // the values in the lowest half-bucket are not spaced like those of the half-buckets above
+        // it, so cannot be moved by offsetting their indexes
+        let mut lowest_half_bucket_counts = Vec::new();
+        for index in 1..self.layout.sub_bucket_half_count {
+            let normalized_index = self.normalize_index(index);
+            let count = self.values.get(normalized_index);
+            if count != T::zero() {
+                lowest_half_bucket_counts.push((self.value_from_index(index) << shift, count));
+                self.values.set(normalized_index, T::zero());
+            }
+        }
+        self.shift_normalizing_index_by_offset(shift as i32 * self.layout.sub_bucket_half_count);
+        for (value, count) in lowest_half_bucket_counts {
+            let normalized_index = self.normalize_index(self.counts_array_index(value));
+            self.values.set(normalized_index, count);
+        }
+        let (max_value, min_non_zero_value) = (self.max_value, self.min_non_zero_value);
         self.max_value = 0;
-        self.min_non_zero_value = std::i64::MAX;
+        self.min_non_zero_value = i64::MAX;
+        let shifted_max_value = self.shifted_left_tracking_value(max_value, shift);
+        self.update_min_and_max(shifted_max_value);
+        if min_non_zero_value != i64::MAX {
+            let shifted_min_non_zero_value = self.shifted_left_tracking_value(min_non_zero_value, shift);
+            self.update_min_and_max(shifted_min_non_zero_value);
+        }
+        Ok(())
+    }
+
+    /// Divides all recorded values by `2^number_of_binary_orders_of_magnitude`.
+    ///
+    /// Fails if any non-zero value would be shifted into the lowest half-bucket, where it could no
+    /// longer be recorded at full precision.
+    pub fn shift_values_right(&mut self, number_of_binary_orders_of_magnitude: u32) -> Result<(), ShiftError> {
+        let shift = number_of_binary_orders_of_magnitude;
+        if shift == 0 || self.total_count == self.get_count_at_index(0) {
+            return Ok(());
+        }
+        let shift_amount = shift as i64 * self.layout.sub_bucket_half_count as i64;
+        if (self.counts_array_index(self.min_non_zero_value) as i64) < shift_amount + self.layout.sub_bucket_half_count as i64 {
+            return Err(ShiftError::Underflow);
+        }
+
+        self.shift_normalizing_index_by_offset(-(shift_amount as i32));
+        let (max_value, min_non_zero_value) = (self.max_value, self.min_non_zero_value);
+        self.max_value = 0;
+        self.min_non_zero_value = i64::MAX;
+        self.update_min_and_max(max_value >> shift);
+        self.update_min_and_max(min_non_zero_value >> shift);
+        Ok(())
     }
 
     pub fn get_counts_array_length(&self) -> i32 {
         self.counts_array_length
     }
 
+    /// The number of indexes by which counts are rotated within the counts array, as a result of
+    /// shifting the histogram's values.
+    pub fn get_normalizing_index_offset(&self) -> i32 {
+        self.normalizing_index_offset
+    }
+
     pub fn get_value_at_percentile(&self, percentile: f64) -> i64 {
-        let requested_percentile = percentile.min(100f64);
-        let count_at_percentile =
-            cmp::max((((requested_percentile / 100f64) * self.get_total_count() as f64) +
-                      0.5f64) as i64,
-                     1i64);
+        let count_at_percentile = self.count_at_percentile(percentile);
         let mut total_to_current_index: i64 = 0;
         for i in 0..self.counts_array_length {
             total_to_current_index += self.get_count_at_index(i);
@@ -263,6 +790,38 @@ impl Histogram {
         0
     }
 
+    /// Returns the value at each of the given percentiles, in the order requested, as
+    /// `get_value_at_percentile` would, but with a single pass over the counts array.
+    pub fn get_values_at_percentiles(&self, percentiles: &[f64]) -> Vec<i64> {
+        let counts_at_percentiles: Vec<i64> =
+            percentiles.iter().map(|&percentile| self.count_at_percentile(percentile)).collect();
+        let mut requests: Vec<usize> = (0..percentiles.len()).collect();
+        requests.sort_by_key(|&request| counts_at_percentiles[request]);
+
+        let mut values = vec![0i64; percentiles.len()];
+        let mut total_to_current_index: i64 = 0;
+        let mut next_index = 0;
+        for request in requests {
+            let count_at_percentile = counts_at_percentiles[request];
+            while total_to_current_index < count_at_percentile && next_index < self.counts_array_length {
+                total_to_current_index += self.get_count_at_index(next_index);
+                next_index += 1;
+            }
+            if total_to_current_index < count_at_percentile {
+                break;
+            }
+
+            let value_at_index: i64 = self.value_from_index(next_index - 1);
+            values[request] = if percentiles[request] == 0f64 {
+                self.lowest_equivalent_value(value_at_index)
+            } else {
+                self.highest_equivalent_value(value_at_index)
+            };
+        }
+
+        values
+    }
+
     pub fn values_are_equivalent(&self, value_one: i64, value_two: i64) -> bool {
         self.lowest_equivalent_value(value_one) == self.lowest_equivalent_value(value_two)
     }
@@ -287,59 +846,123 @@ impl Histogram {
             counts_array_index
         };
         let index = cmp::min(counts_idx, self.counts_array_length - 1);
-        self.values[index as usize]
+        self.get_count_at_index(index)
+    }
+
+    pub fn set_auto_resize(&mut self, auto_resize: bool) {
+        self.auto_resize = auto_resize;
+    }
+
+    pub fn is_auto_resize(&self) -> bool {
+        self.auto_resize
+    }
+
+    /// The start of the period covered by this histogram, in milliseconds since the epoch
+    pub fn get_start_time(&self) -> i64 {
+        self.start_time
+    }
+
+    pub fn set_start_time(&mut self, start_time: i64) {
+        self.start_time = start_time;
+    }
+
+    /// The end of the period covered by this histogram, in milliseconds since the epoch
+    pub fn get_end_time(&self) -> i64 {
+        self.end_time
+    }
+
+    pub fn set_end_time(&mut self, end_time: i64) {
+        self.end_time = end_time;
+    }
+
+    /// An optional label identifying the source of the recorded values, e.g. in an interval log
+    pub fn get_tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
     }
 
     pub fn get_total_count(&self) -> i64 {
         self.total_count
     }
 
-    pub fn get_recorded_values<F, T>(&self, f: F, t: &mut T)
-        where F: Fn(Option<(i64, &HistogramIterationValue, &mut T)>)
-    {
-        let mut iter = new_iterator(self);
-        iter.reset(self.total_count, self.unit_magnitude);
-        let mut index = 0;
-        while iter.has_next() {
-            f(Some((index, iter.next(), t)));
-            index += 1;
-        }
-        f(None)
+    /// The ratio by which recorded integer values are multiplied to obtain the values they
+    /// represent, e.g. `0.001` for a histogram recording microseconds that reports milliseconds.
+    /// Defaults to `1.0`, and is stored when the histogram is serialised.
+    pub fn get_integer_to_double_value_conversion_ratio(&self) -> f64 {
+        self.integer_to_double_value_conversion_ratio
     }
 
-    pub fn collect_recorded_values(&self, container: &mut Vec<HistogramIterationValue>) {
-        let mut iter = new_iterator(self);
-        iter.reset(self.total_count, self.unit_magnitude);
+    pub fn set_integer_to_double_value_conversion_ratio(&mut self, integer_to_double_value_conversion_ratio: f64) {
+        self.integer_to_double_value_conversion_ratio = integer_to_double_value_conversion_ratio;
+    }
 
-        while iter.has_next() {
-            let mut value = HistogramIterationValue::new();
-            iter.next().copy_to(&mut value);
-            container.push(value);
-        }
+    pub fn get_scaled_min_value(&self) -> f64 {
+        self.get_min_value() as f64 * self.integer_to_double_value_conversion_ratio
     }
 
-    pub fn get_all_values<F, T>(&self, f: F, t: &mut T)
-        where F: Fn(Option<(i64, &HistogramIterationValue, &mut T)>)
-    {
-        let mut iter = new_all_values_iterator(self);
-        iter.reset(self.total_count, self.unit_magnitude);
-        let mut index = 0;
-        while iter.has_next() {
-            f(Some((index, iter.next(), t)));
-            index += 1;
-        }
-        f(None)
+    pub fn get_scaled_max_value(&self) -> f64 {
+        self.get_max_value() as f64 * self.integer_to_double_value_conversion_ratio
     }
 
-    pub fn collect_all_values(&self, container: &mut Vec<HistogramIterationValue>) {
-        let mut iter = new_all_values_iterator(self);
-        iter.reset(self.total_count, self.unit_magnitude);
+    pub fn get_scaled_mean(&self) -> f64 {
+        self.get_mean() * self.integer_to_double_value_conversion_ratio
+    }
 
-        while iter.has_next() {
-            let mut value = HistogramIterationValue::new();
-            iter.next().copy_to(&mut value);
-            container.push(value);
-        }
+    pub fn get_scaled_std_deviation(&self) -> f64 {
+        self.get_std_deviation() * self.integer_to_double_value_conversion_ratio
+    }
+
+    pub fn get_scaled_value_at_percentile(&self, percentile: f64) -> f64 {
+        self.get_value_at_percentile(percentile) as f64 * self.integer_to_double_value_conversion_ratio
+    }
+
+    /// Returns an iterator over the values recorded in this histogram, one per populated index.
+    pub fn iter_recorded(&self) -> RecordedValuesIterator<'_, T, S> {
+        new_iterator(self)
+    }
+
+    /// Returns an iterator over the values recorded in this histogram from the highest down, with
+    /// counts accumulated from the top.
+    pub fn iter_recorded_rev(&self) -> RecordedValuesReverseIterator<'_, T, S> {
+        new_reverse_iterator(self)
+    }
+
+    /// Returns an iterator over every index of the counts array, whether or not any values were
+    /// recorded there.
+    pub fn iter_all(&self) -> AllValuesIterator<'_, T, S> {
+        new_all_values_iterator(self)
+    }
+
+    /// Returns an iterator over the values at percentile levels stepping towards 100%, with
+    /// `percentile_ticks_per_half_distance` levels between each halving of the distance to 100%.
+    pub fn percentiles(&self, percentile_ticks_per_half_distance: i32) -> PercentileIterator<'_, T, S> {
+        new_percentile_iterator(self, percentile_ticks_per_half_distance)
+    }
+
+    /// Returns an iterator over the counts in each `value_units_per_bucket` wide range of values,
+    /// up to the range holding the highest recorded value.
+    pub fn linear_bucket_values(&self, value_units_per_bucket: i64) -> LinearBucketValuesIterator<'_, T, S> {
+        new_linear_bucket_values_iterator(self, value_units_per_bucket)
+    }
+
+    /// Returns an iterator over the counts in buckets whose upper bounds start at
+    /// `value_units_in_first_bucket` and grow by a factor of `log_base`.
+    pub fn logarithmic_bucket_values(&self,
+                                     value_units_in_first_bucket: i64,
+                                     log_base: f64)
+                                     -> LogarithmicBucketValuesIterator<'_, T, S> {
+        new_logarithmic_bucket_values_iterator(self, value_units_in_first_bucket, log_base)
+    }
+
+    pub fn collect_recorded_values(&self, container: &mut Vec<HistogramIterationValue>) {
+        container.extend(self.iter_recorded());
+    }
+
+    pub fn collect_all_values(&self, container: &mut Vec<HistogramIterationValue>) {
+        container.extend(self.iter_all());
     }
     
     pub fn serialise(&self, target_buffer: &mut Vec<u8>) {
@@ -347,27 +970,26 @@ impl Histogram {
 
     	let index_of_payload_length = target_buffer.len() as i32;
     	put_i32(0, target_buffer);
-    	// normalising index offset - always 0
-    	put_i32(0, target_buffer);
+    	put_i32(self.normalizing_index_offset, target_buffer);
     	put_i32(self.number_of_significant_digits, target_buffer);
     	put_i64(self.lowest_discernible_value, target_buffer);
     	put_i64(self.highest_trackable_value, target_buffer);
-    	// value conversion ratio - currently unsupported
-    	put_i64(0, target_buffer);
+    	put_i64(self.integer_to_double_value_conversion_ratio.to_bits() as i64, target_buffer);
     	
     	let counts_payload_length = self.fill_buffer_from_counts_array(target_buffer);
     	
     	put_i32_at_offset(counts_payload_length, target_buffer, index_of_payload_length);
     }
     
-    fn establish_internal_tracking_values(&mut self, length_to_cover: i32) {
+    pub(crate) fn establish_internal_tracking_values(&mut self, length_to_cover: i32) {
     	let mut max_index: i32 = -1;
     	let mut min_non_zero_index: i32 = -1;
     	let mut observed_total_count: i64 = 0;
+    	self.max_value = 0;
+    	self.min_non_zero_value = i64::MAX;
     	
     	for index in 0..length_to_cover {
     		let count_at_index = self.get_count_at_index(index);
-            println!("count_at_index[{}] = {}", index, count_at_index);
     		if count_at_index > 0 {
     			observed_total_count += count_at_index;
     			max_index = index;
@@ -424,7 +1046,7 @@ impl Histogram {
     	(target_buffer.len() - buffer_start_length) as i32
     }
     
-    fn fill_counts_array_from_source_buffer(&mut self, source_buffer: &Vec<u8>, offset: i32, length_in_bytes: i32, word_size_in_bytes: i32) -> i32 {
+    fn fill_counts_array_from_source_buffer(&mut self, source_buffer: &[u8], offset: i32, length_in_bytes: i32, word_size_in_bytes: i32) -> Option<i32> {
     	let end_position = offset + length_in_bytes;
     	let mut offset_within_payload = offset;
     	let mut dst_index = 0;
@@ -436,49 +1058,115 @@ impl Histogram {
     			panic!("Only 8-byte word size is supported. Is input buffer in v2 format?");
     		}
     		let (value, length) = decode(source_buffer, offset_within_payload);
-            println!("Decoded value {} of length {} from source buffer", value, length);
     		let count = value;
     		offset_within_payload += length;
     		if count < 0 {
-    			let zc = -value;
-    			if zc > std::i64::MAX {
-    				panic!("An encoded zero count of > i64::MAX was encountered in the source");
-    			}
-    			
-    			zeroes_count = zc as i32;
+    			zeroes_count = -value as i32;
     		}
     		
     		if zeroes_count > 0 {
     			dst_index += zeroes_count;
-                println!("Advanced dst_index to {} due to {} zeroes", dst_index, zeroes_count);
     		} else {
-                println!("Setting count to {} at index {}", count, dst_index);
-    			self.set_count_at_index(dst_index, count);
+    			if !self.set_count_at_index(dst_index, count) {
+    				return None;
+    			}
                 dst_index += 1;
     		}
     	}
     	
-    	dst_index
+    	Some(dst_index)
     }
 
-    fn increment_total_count(&mut self) {
-        self.total_count += 1;
+    /// Maps each of `other`'s populated indexes to the index in this histogram covering the same
+    /// values (or, if the layouts differ, the index of its median equivalent value), returning the
+    /// summed counts for each index in ascending order.
+    fn counts_by_index_of<U: Counter, R: CountsArray<U>>(&self, other: &Histogram<U, R>) -> Vec<(i32, i64)> {
+        let same_layout = self.layout == other.layout;
+        let mut counts: Vec<(i32, i64)> = Vec::new();
+        for index in 0..other.counts_array_length {
+            let count = other.get_count_at_index(index);
+            if count == 0 {
+                continue;
+            }
+            let counts_index = if same_layout {
+                index
+            } else {
+                self.counts_array_index(other.median_equivalent_value(other.value_from_index(index)))
+            };
+            match counts.last_mut() {
+                Some(&mut (last_index, ref mut last_count)) if last_index == counts_index => *last_count += count,
+                _ => counts.push((counts_index, count)),
+            }
+        }
+        counts
     }
 
-    fn counts_array_index_by_bucket(&self, bucket_index: i32, sub_bucket_index: i32) -> i32 {
-        let bucket_base_index = (bucket_index + 1) << self.sub_bucket_half_count_magnitude;
-        let offset_in_bucket = sub_bucket_index - self.sub_bucket_half_count;
+    /// Shifts a min or max value, keeping it within the index its count was moved to. A count in
+    /// the lowest half-bucket moves to the first of the indexes its scaled range spans, so the
+    /// shifted value may need to be clamped to that index.
+    fn shifted_left_tracking_value(&self, value: i64, shift: u32) -> i64 {
+        let moved_to_value = self.lowest_equivalent_value(value) << shift;
+        if self.counts_array_index(value << shift) == self.counts_array_index(moved_to_value) {
+            value << shift
+        } else {
+            moved_to_value
+        }
+    }
 
+    /// Moves every count but the one at index 0 up by `offset_to_add` indexes (or down, if
+    /// negative) without touching the counts array.
+    fn shift_normalizing_index_by_offset(&mut self, offset_to_add: i32) {
+        let zero_index = self.normalize_index(0);
+        let count_at_zero_index = self.values.get(zero_index);
+        self.values.set(zero_index, T::zero());
+        self.normalizing_index_offset =
+            (self.normalizing_index_offset + offset_to_add).rem_euclid(self.counts_array_length);
+        let zero_index = self.normalize_index(0);
+        self.values.set(zero_index, count_at_zero_index);
+    }
 
-        bucket_base_index + offset_in_bucket
+    /// Maps an index to its position in the counts array, which is rotated by the normalizing
+    /// index offset.
+    fn normalize_index(&self, index: i32) -> usize {
+        if self.normalizing_index_offset == 0 {
+            index as usize
+        } else {
+            (index - self.normalizing_index_offset).rem_euclid(self.counts_array_length) as usize
+        }
     }
 
-    fn increment_count_at_index(&mut self, counts_index: i32) {
-        self.values[counts_index as usize] += 1;
+    fn add_to_count_at_index(&mut self, counts_index: i32, count: i64) -> Result<(), RecordError> {
+        let new_total_count = match self.total_count.checked_add(count) {
+            Some(total_count) => total_count,
+            None => return Err(RecordError::CountOverflow),
+        };
+        let new_count = self.get_count_at_index(counts_index).checked_add(count).and_then(T::from_i64);
+        match new_count {
+            Some(new_count) => self.values.set(self.normalize_index(counts_index), new_count),
+            None => return Err(RecordError::CountOverflow),
+        }
+        self.total_count = new_total_count;
+        Ok(())
     }
     
-    fn set_count_at_index(&mut self, counts_index: i32, value: i64) {
-    	self.values[counts_index as usize] = value;
+    pub(crate) fn set_count_at_index(&mut self, counts_index: i32, value: i64) -> bool {
+    	match T::from_i64(value) {
+    		Some(count) => {
+    			self.values.set(self.normalize_index(counts_index), count);
+    			true
+    		}
+    		None => false,
+    	}
+    }
+
+    /// Widens the period covered by this histogram to include `other`'s, ignoring unset times.
+    fn merge_time_range<U: Counter, R: CountsArray<U>>(&mut self, other: &Histogram<U, R>) {
+        if other.start_time != 0 && (self.start_time == 0 || other.start_time < self.start_time) {
+            self.start_time = other.start_time;
+        }
+        if other.end_time > self.end_time {
+            self.end_time = other.end_time;
+        }
     }
 
     fn update_min_and_max(&mut self, value: i64) {
@@ -490,29 +1178,233 @@ impl Histogram {
         }
     }
 
+    fn record_single_value(&mut self, value: i64) -> Result<(), RecordError> {
+        self.record_count_at_value(1, value)
+    }
+
+    fn record_count_at_value_with_expected_interval(&mut self,
+                                                    count: i64,
+                                                    value: i64,
+                                                    expected_interval_between_value_samples: i64)
+                                                    -> Result<(), RecordError> {
+        record_with_expected_interval(value,
+                                      expected_interval_between_value_samples,
+                                      |value_to_record| self.record_count_at_value(count, value_to_record))
+    }
+
+    /// Creates an empty histogram with the same configuration and counts array length.
+    fn new_empty_copy(&self) -> Histogram<T, S> {
+        let config = HistogramConfig {
+            lowest_discernible_value: self.lowest_discernible_value,
+            highest_trackable_value: self.highest_trackable_value,
+            number_of_significant_digits: self.number_of_significant_digits,
+            auto_resize: self.auto_resize,
+        };
+        let mut histogram: Histogram<T, S> = match config.build_with_storage() {
+            Ok(histogram) => histogram,
+            Err(e) => panic!("Invalid histogram configuration: {}", e),
+        };
+        histogram.integer_to_double_value_conversion_ratio = self.integer_to_double_value_conversion_ratio;
+        histogram
+    }
+
+    fn record_count_at_value(&mut self, count: i64, value: i64) -> Result<(), RecordError> {
+        if value < 0 {
+            return Err(RecordError::NegativeValue(value));
+        }
+        if count < 0 {
+            return Err(RecordError::NegativeCount(count));
+        }
+        let counts_index = self.counts_array_index(value);
+        if counts_index >= self.counts_array_length {
+            if !self.auto_resize {
+                return Err(RecordError::ValueOutOfRange(value));
+            }
+            self.try_resize(value)?;
+        }
+        self.add_to_count_at_index(counts_index, count)?;
+        self.update_min_and_max(value);
+        Ok(())
+    }
+
+    fn count_at_percentile(&self, percentile: f64) -> i64 {
+        let requested_percentile = percentile.min(100f64);
+        cmp::max((((requested_percentile / 100f64) * self.get_total_count() as f64) + 0.5f64) as i64,
+                 1i64)
+    }
+
+    pub(crate) fn counts_array_index(&self, value: i64) -> i32 {
+        self.layout.counts_array_index(value)
+    }
+
+    fn median_equivalent_value(&self, value: i64) -> i64 {
+        self.layout.median_equivalent_value(value)
+    }
+
+    /// Grows the counts array to cover `value` so that it can be recorded, leaving the histogram
+    /// unchanged if the counts array cannot cover it.
+    fn try_resize(&mut self, value: i64) -> Result<(), RecordError> {
+        if self.counts_array_index(value) >= self.layout.counts_array_length_needed(value) {
+            return Err(RecordError::ValueOutOfRange(value));
+        }
+        self.resize(value);
+        Ok(())
+    }
+
+    pub(crate) fn resize(&mut self, new_highest_trackable_value: i64) {
+        // the rotation of the counts array depends on its length, so counts stored at a non-zero
+        // offset have to be re-placed once it changes
+        let mut normalized_counts = Vec::new();
+        if self.normalizing_index_offset != 0 {
+            for index in 0..self.counts_array_length {
+                let count = self.values.get(self.normalize_index(index));
+                if count != T::zero() {
+                    normalized_counts.push((index, count));
+                }
+            }
+            self.values.clear();
+        }
+        self.establish_size(new_highest_trackable_value);
+        self.values.resize(self.counts_array_length as usize);
+        for (index, count) in normalized_counts {
+            let normalized_index = self.normalize_index(index);
+            self.values.set(normalized_index, count);
+        }
+        // the new counts array may cover values beyond those requested
+        self.highest_trackable_value =
+            self.highest_equivalent_value(self.value_from_index(self.counts_array_length - 1));
+    }
+
+    fn establish_size(&mut self, new_highest_trackable_value: i64) {
+        self.counts_array_length = self.layout.counts_array_length_needed(new_highest_trackable_value);
+        self.bucket_count = self.layout.buckets_needed(new_highest_trackable_value);
+        self.highest_trackable_value = new_highest_trackable_value;
+    }
+}
+
+impl<T: Counter, S: CountsArray<T>> Clone for Histogram<T, S> {
+    fn clone(&self) -> Histogram<T, S> {
+        let mut histogram = self.new_empty_copy();
+        self.copy_into(&mut histogram);
+        histogram
+    }
+
+    fn clone_from(&mut self, source: &Histogram<T, S>) {
+        source.copy_into(self);
+    }
+}
+
+/// Histograms are equal if they share the same precision and conversion ratio and hold the same
+/// counts, regardless of counter type, storage or the length of their counts arrays.
+impl<T: Counter, S: CountsArray<T>, U: Counter, R: CountsArray<U>> PartialEq<Histogram<U, R>> for Histogram<T, S> {
+    fn eq(&self, other: &Histogram<U, R>) -> bool {
+        if self.lowest_discernible_value != other.lowest_discernible_value ||
+           self.number_of_significant_digits != other.number_of_significant_digits ||
+           self.integer_to_double_value_conversion_ratio != other.integer_to_double_value_conversion_ratio ||
+           self.total_count != other.total_count {
+            return false;
+        }
+        if self.highest_equivalent_value(self.max_value) != other.highest_equivalent_value(other.max_value) ||
+           self.lowest_equivalent_value(self.get_min_value()) != other.lowest_equivalent_value(other.get_min_value()) {
+            return false;
+        }
+        // the counts arrays may differ in length, e.g. after resizing, but are empty above the max
+        let counts_array_length = self.counts_array_index(self.max_value) + 1;
+        (0..counts_array_length).all(|index| self.get_count_at_index(index) == other.get_count_at_index(index))
+    }
+}
+
+impl<T: Counter, S: CountsArray<T>> fmt::Display for Histogram<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Histogram[")?;
+        write!(f, "total_count: {}, ", self.total_count)?;
+        write!(f,
+                    "highest_trackable_value: {}, ",
+                    self.highest_trackable_value)?;
+        write!(f,
+                    "lowest_discernible_value: {}, ",
+                    self.lowest_discernible_value)?;
+        write!(f,
+                    "number_of_significant_digits: {}, ",
+                    self.number_of_significant_digits)?;
+        write!(f, "bucket_count: {}, ", self.bucket_count)?;
+        write!(f, "sub_bucket_count: {}, ", self.layout.sub_bucket_count)?;
+        write!(f, "counts_array_length: {}, ", self.counts_array_length)?;
+        write!(f, "word_size_in_bytes: {}, ", self.word_size_in_bytes)?;
+        write!(f, "]")
+
+    }
+}
+
+/// The mapping between values and counts array indices, which is fixed for a given lowest
+/// discernible value and number of significant digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BucketLayout {
+    pub(crate) unit_magnitude: i32,
+    pub(crate) sub_bucket_count: i32,
+    sub_bucket_half_count_magnitude: i32,
+    pub(crate) sub_bucket_half_count: i32,
+    leading_zero_count_base: i32,
+    sub_bucket_mask: i64,
+}
+
+impl BucketLayout {
+    pub(crate) fn counts_array_length_needed(&self, highest_trackable_value: i64) -> i32 {
+        determine_array_length_needed(highest_trackable_value,
+                                      self.sub_bucket_count,
+                                      self.unit_magnitude)
+    }
+
+    pub(crate) fn buckets_needed(&self, highest_trackable_value: i64) -> i32 {
+        get_buckets_needed_to_cover_value(highest_trackable_value,
+                                          self.sub_bucket_count,
+                                          self.unit_magnitude)
+    }
+
+    pub(crate) fn value_from_index(&self, index: i32) -> i64 {
+        let mut bucket_index: i32 = (index >> self.sub_bucket_half_count_magnitude) - 1;
+        let mut sub_bucket_index: i32 = (index & (self.sub_bucket_half_count - 1)) +
+                                        self.sub_bucket_half_count;
+        if bucket_index < 0 {
+            sub_bucket_index -= self.sub_bucket_half_count;
+            bucket_index = 0;
+        }
+        self.value_from_index_by_bucket(bucket_index, sub_bucket_index)
+    }
+
+    pub(crate) fn lowest_equivalent_value(&self, value: i64) -> i64 {
+        let bucket_index = self.get_bucket_index(value);
+        let sub_bucket_index = self.get_sub_bucket_index(value, bucket_index);
+        self.value_from_index_by_bucket(bucket_index, sub_bucket_index)
+    }
+
+    pub(crate) fn highest_equivalent_value(&self, value: i64) -> i64 {
+        // the range of the top bucket may extend beyond i64::MAX
+        self.lowest_equivalent_value(value).saturating_add(self.size_of_equivalent_value_range(value) - 1)
+    }
+
+    fn counts_array_index_by_bucket(&self, bucket_index: i32, sub_bucket_index: i32) -> i32 {
+        let bucket_base_index = (bucket_index + 1) << self.sub_bucket_half_count_magnitude;
+        let offset_in_bucket = sub_bucket_index - self.sub_bucket_half_count;
+
+        bucket_base_index + offset_in_bucket
+    }
+
     fn get_bucket_index(&self, value: i64) -> i32 {
         (self.leading_zero_count_base as i64 -
-         (value | self.sub_bucket_mask as i64).leading_zeros() as i64) as i32
+         (value | self.sub_bucket_mask).leading_zeros() as i64) as i32
     }
 
     fn get_sub_bucket_index(&self, value: i64, bucket_index: i32) -> i32 {
         ((value as u64) >> (bucket_index + self.unit_magnitude)) as i32
     }
 
-    fn counts_array_index(&self, value: i64) -> i32 {
+    pub(crate) fn counts_array_index(&self, value: i64) -> i32 {
         let bucket_index = self.get_bucket_index(value);
         let sub_bucket_index = self.get_sub_bucket_index(value, bucket_index);
         self.counts_array_index_by_bucket(bucket_index, sub_bucket_index)
     }
 
-    fn record_single_value(&mut self, value: i64) {
-        let counts_index = self.counts_array_index(value);
-        // TODO handle out of bounds exceptions
-        self.increment_count_at_index(counts_index);
-        self.update_min_and_max(value);
-        self.increment_total_count();
-    }
-
     fn value_from_index_by_bucket(&self, bucket_index: i32, sub_bucket_index: i32) -> i64 {
         (sub_bucket_index as i64) << (bucket_index + self.unit_magnitude)
     }
@@ -528,43 +1420,15 @@ impl Histogram {
         1i64 << (self.unit_magnitude + mult)
     }
 
-    fn next_non_equivalent_value(&self, value: i64) -> i64 {
-        self.lowest_equivalent_value(value) + self.size_of_equivalent_value_range(value)
-    }
-
-
-    fn median_equivalent_value(&self, value: i64) -> i64 {
+    pub(crate) fn median_equivalent_value(&self, value: i64) -> i64 {
         self.lowest_equivalent_value(value) + (self.size_of_equivalent_value_range(value) >> 1)
     }
 }
 
-impl fmt::Display for Histogram {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f, "Histogram["));
-        try!(write!(f, "total_count: {}, ", self.total_count));
-        try!(write!(f,
-                    "highest_trackable_value: {}, ",
-                    self.highest_trackable_value));
-        try!(write!(f,
-                    "lowest_discernible_value: {}, ",
-                    self.lowest_discernible_value));
-        try!(write!(f,
-                    "number_of_significant_digits: {}, ",
-                    self.number_of_significant_digits));
-        try!(write!(f, "bucket_count: {}, ", self.bucket_count));
-        try!(write!(f, "sub_bucket_count: {}, ", self.sub_bucket_count));
-        try!(write!(f, "counts_array_length: {}, ", self.counts_array_length));
-        try!(write!(f, "word_size_in_bytes: {}, ", self.word_size_in_bytes));
-        write!(f, "]")
-
-    }
-}
-
 fn determine_array_length_needed(highest_trackable_value: i64,
                                  sub_bucket_count: i32,
                                  unit_magnitude: i32)
                                  -> i32 {
-    // TODO error handling if highest < 2 * lowest_discernible
     let buckets_needed_to_cover_value = get_buckets_needed_to_cover_value(highest_trackable_value,
                                                                           sub_bucket_count,
                                                                           unit_magnitude);
@@ -576,11 +1440,11 @@ fn get_buckets_needed_to_cover_value(highest_trackable_value: i64,
                                      sub_bucket_count: i32,
                                      unit_magnitude: i32)
                                      -> i32 {
-    let mut smallest_untrackable_value = ((sub_bucket_count as i64) << unit_magnitude) as i64;
+    let mut smallest_untrackable_value = (sub_bucket_count as i64) << unit_magnitude;
     let mut buckets_needed: i32 = 1;
     while smallest_untrackable_value <= highest_trackable_value {
 
-        if smallest_untrackable_value > (std::i64::MAX / 2) {
+        if smallest_untrackable_value > (i64::MAX / 2) {
             buckets_needed += 1;
             return buckets_needed;
         }
@@ -590,3 +1454,25 @@ fn get_buckets_needed_to_cover_value(highest_trackable_value: i64,
     }
     buckets_needed
 }
+
+/// Passes `value` to `record`, followed by each of the values that would have been recorded had
+/// samples been taken every `expected_interval_between_value_samples`, stopping at the first
+/// error. Shared by every histogram type that corrects for coordinated omission at record time.
+pub(crate) fn record_with_expected_interval<V, E, F>(value: V,
+                                                     expected_interval_between_value_samples: V,
+                                                     mut record: F)
+                                                     -> Result<(), E>
+    where V: Copy + Default + PartialOrd + Sub<Output = V>,
+          F: FnMut(V) -> Result<(), E>
+{
+    record(value)?;
+    if expected_interval_between_value_samples <= V::default() {
+        return Ok(());
+    }
+    let mut missing_value = value - expected_interval_between_value_samples;
+    while missing_value >= expected_interval_between_value_samples {
+        record(missing_value)?;
+        missing_value = missing_value - expected_interval_between_value_samples;
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
+use std::cmp;
 use std::fmt;
 use histogram::*;
+use counter::*;
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct HistogramIterationValue {
     value_iterated_to: i64,
     value_iterated_from: i64,
@@ -27,36 +29,6 @@ impl HistogramIterationValue {
         }
     }
 
-    fn set(&mut self,
-           _value_iterated_to: i64,
-           _value_iterated_from: i64,
-           _count_at_value_iterated_to: i64,
-           _count_added_in_this_iteration_step: i64,
-           _total_count_to_this_value: i64,
-           _total_value_to_this_value: i64,
-           _percentile: f64,
-           _percentile_level_iterated_to: f64) {
-        self.value_iterated_to = _value_iterated_to;
-        self.value_iterated_from = _value_iterated_from;
-        self.count_at_value_iterated_to = _count_at_value_iterated_to;
-        self.count_added_in_this_iteration_step = _count_added_in_this_iteration_step;
-        self.total_count_to_this_value = _total_count_to_this_value;
-        self.total_value_to_this_value = _total_value_to_this_value;
-        self.percentile = _percentile;
-        self.percentile_level_iterated_to = _percentile_level_iterated_to;
-    }
-
-    fn reset(&mut self) {
-        self.value_iterated_to = 0;
-        self.value_iterated_from = 0;
-        self.count_at_value_iterated_to = 0;
-        self.count_added_in_this_iteration_step = 0;
-        self.total_count_to_this_value = 0;
-        self.total_value_to_this_value = 0;
-        self.percentile = 0.0;
-        self.percentile_level_iterated_to = 0.0;
-    }
-
     pub fn copy_to(&self, target: &mut HistogramIterationValue) {
         target.value_iterated_to = self.value_iterated_to;
         target.value_iterated_from = self.value_iterated_from;
@@ -87,10 +59,30 @@ impl HistogramIterationValue {
     pub fn get_total_count_to_this_value(&self) -> i64 {
         self.total_count_to_this_value
     }
+
+    pub fn get_value_iterated_from(&self) -> i64 {
+        self.value_iterated_from
+    }
+
+    /// The percentile of recorded values at or below `value_iterated_to`
+    pub fn get_percentile(&self) -> f64 {
+        self.percentile
+    }
+
+    /// The percentile level the iterator was stepping to when it produced this value, which may
+    /// be below `percentile` if the value covers several levels
+    pub fn get_percentile_level_iterated_to(&self) -> f64 {
+        self.percentile_level_iterated_to
+    }
+}
+
+impl Default for HistogramIterationValue {
+    fn default() -> HistogramIterationValue {
+        HistogramIterationValue::new()
+    }
 }
 
 struct IteratorSharedState {
-    saved_histogram_total_raw_count: i64,
     current_index: i32,
     current_value_at_index: i64,
     next_value_at_index: i64,
@@ -102,35 +94,34 @@ struct IteratorSharedState {
     count_at_this_value: i64,
     fresh_sub_bucket: bool,
     visited_index: i32,
-    current_iteration_value: HistogramIterationValue,
 }
 
 impl IteratorSharedState {
-    fn exhausted_sub_buckets(&self, histogram: &Histogram) -> bool {
-        self.current_index >= histogram.get_counts_array_length()
+    fn new<T: Counter, S: CountsArray<T>>(histogram: &Histogram<T, S>) -> IteratorSharedState {
+        IteratorSharedState {
+            current_index: 0,
+            current_value_at_index: 0,
+            next_value_at_index: histogram.value_from_index(1),
+            prev_value_iterated_to: 0,
+            total_count_to_prev_index: 0,
+            total_count_to_current_index: 0,
+            total_value_to_current_index: 0,
+            array_total_count: histogram.get_total_count(),
+            count_at_this_value: 0,
+            fresh_sub_bucket: true,
+            visited_index: -1,
+        }
     }
-    
-    fn reset(&mut self, total_count: i64, unit_magnitude: i32) {
-        self.saved_histogram_total_raw_count = total_count;
-        self.array_total_count = total_count;
-        self.current_index = 0;
-        self.current_value_at_index = 0;
-        self.next_value_at_index = 1 << unit_magnitude;
-        self.prev_value_iterated_to = 0;
-        self.total_count_to_prev_index = 0;
-        self.total_count_to_current_index = 0;
-        self.total_value_to_current_index = 0;
-        self.count_at_this_value = 0;
-        self.fresh_sub_bucket = true;
-        self.visited_index = -1;
-        self.current_iteration_value.reset();
+
+    fn exhausted_sub_buckets<T: Counter, S: CountsArray<T>>(&self, histogram: &Histogram<T, S>) -> bool {
+        self.current_index >= histogram.get_counts_array_length()
     }
     
     fn get_percentile_iterated_to(&self) -> f64 {
         (100.0f64 * self.total_count_to_current_index as f64) / self.array_total_count as f64
     }
     
-    fn get_value_iterated_to(&self, histogram: &Histogram) -> i64 {
+    fn get_value_iterated_to<T: Counter, S: CountsArray<T>>(&self, histogram: &Histogram<T, S>) -> i64 {
         histogram.highest_equivalent_value(self.current_value_at_index)
     }
 
@@ -138,15 +129,15 @@ impl IteratorSharedState {
         self.visited_index = self.current_index;
     }
     
-    fn increment_sub_bucket(&mut self, histogram: &Histogram) {
+    fn increment_sub_bucket<T: Counter, S: CountsArray<T>>(&mut self, histogram: &Histogram<T, S>) {
         self.fresh_sub_bucket = true;
         self.current_index += 1;
         self.current_value_at_index = histogram.value_from_index(self.current_index);
         self.next_value_at_index = histogram.value_from_index(self.current_index + 1);
     }
     
-    fn next<F>(&mut self, histogram: &Histogram, level_reached_function: F) -> &HistogramIterationValue 
-    		where F: Fn(&mut IteratorSharedState, &Histogram) -> bool {
+    fn next<T: Counter, S: CountsArray<T>, F>(&mut self, histogram: &Histogram<T, S>, level_reached_function: F) -> HistogramIterationValue 
+    		where F: Fn(&mut IteratorSharedState, &Histogram<T, S>) -> bool {
         while !self.exhausted_sub_buckets(histogram) {
             self.count_at_this_value = histogram.get_count_at_index(self.current_index);
             if self.fresh_sub_bucket {
@@ -160,22 +151,23 @@ impl IteratorSharedState {
             if level_reached_function(self, histogram) {
                 let value_iterated_to = self.get_value_iterated_to(histogram);
                 let percentile_iterated_to = self.get_percentile_iterated_to();
-                self.current_iteration_value.set(value_iterated_to,
-                                                 self.prev_value_iterated_to,
-                                                 self.count_at_this_value,
-                                                 (self.total_count_to_current_index -
-                                                  self.total_count_to_prev_index),
-                                                 self.total_count_to_current_index,
-                                                 self.total_value_to_current_index,
-                                                 ((100.0f64 *
-                                                   self.total_count_to_current_index as f64) /
-                                                  (self.array_total_count as f64)),
-                                                 percentile_iterated_to);
+                let iteration_value = HistogramIterationValue {
+                    value_iterated_to,
+                    value_iterated_from: self.prev_value_iterated_to,
+                    count_at_value_iterated_to: self.count_at_this_value,
+                    count_added_in_this_iteration_step: self.total_count_to_current_index -
+                                                        self.total_count_to_prev_index,
+                    total_count_to_this_value: self.total_count_to_current_index,
+                    total_value_to_this_value: self.total_value_to_current_index,
+                    percentile: (100.0f64 * self.total_count_to_current_index as f64) /
+                                self.array_total_count as f64,
+                    percentile_level_iterated_to: percentile_iterated_to,
+                };
                 self.prev_value_iterated_to = value_iterated_to;
                 self.total_count_to_prev_index = self.total_count_to_current_index;
                 self.increment_iteration_level();
 
-                return &self.current_iteration_value;
+                return iteration_value;
             }
 
             self.increment_sub_bucket(histogram);
@@ -184,113 +176,329 @@ impl IteratorSharedState {
     }
 }
 
-pub struct AllValuesIterator<'a> {
-    histogram: &'a Histogram,
+/// Steps through every index of the counts array, whether or not anything was recorded there.
+pub struct AllValuesIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
     state: IteratorSharedState
 }
 
-pub fn new_all_values_iterator<'a>(_histogram: &'a Histogram) -> AllValuesIterator {
+pub fn new_all_values_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>) -> AllValuesIterator<'a, T, S> {
     AllValuesIterator {
         histogram: _histogram,
-        state: IteratorSharedState {
-	        saved_histogram_total_raw_count: 0,
-	        current_index: 0,
-	        current_value_at_index: 0,
-	        next_value_at_index: 0,
-	        prev_value_iterated_to: 0,
-	        total_count_to_prev_index: 0,
-	        total_count_to_current_index: 0,
-	        total_value_to_current_index: 0,
-	        array_total_count: 0,
-	        count_at_this_value: 0,
-	        fresh_sub_bucket: true,
-	        visited_index: -1,
-	        current_iteration_value: HistogramIterationValue::new(),
-        }
+        state: IteratorSharedState::new(_histogram),
     }
 }
 
-impl<'a> AllValuesIterator<'a> {
-    pub fn has_next(&mut self) -> bool {
+impl<'a, T: Counter, S: CountsArray<T>> AllValuesIterator<'a, T, S> {
+    fn has_next(&self) -> bool {
+        // the current index is the last one visited, or 0 before the first step
+        self.state.visited_index < 0 ||
         self.state.current_index < (self.histogram.get_counts_array_length() - 1)
     }
+}
 
-    pub fn reset(&mut self, total_count: i64, unit_magnitude: i32) {
-        self.state.reset(total_count, unit_magnitude);
-    }
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for AllValuesIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
 
-    pub fn next(&mut self) -> &HistogramIterationValue {
-    	self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, _histogram: &Histogram| {
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        if !self.has_next() {
+            return None;
+        }
+    	Some(self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, _histogram: &Histogram<T, S>| {
     			iterator_state.visited_index != iterator_state.current_index
-    	})
+    	}))
     }
 }
 
-
-pub struct RecordedValuesIterator<'a> {
-    histogram: &'a Histogram,
+/// Steps through the indexes of the counts array at which values were recorded.
+pub struct RecordedValuesIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
     state: IteratorSharedState
 }
 
-pub fn new_iterator<'a>(_histogram: &'a Histogram) -> RecordedValuesIterator {
+pub fn new_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>) -> RecordedValuesIterator<'a, T, S> {
     RecordedValuesIterator {
         histogram: _histogram,
-        state: IteratorSharedState {
-	        saved_histogram_total_raw_count: 0,
-	        current_index: 0,
-	        current_value_at_index: 0,
-	        next_value_at_index: 0,
-	        prev_value_iterated_to: 0,
-	        total_count_to_prev_index: 0,
-	        total_count_to_current_index: 0,
-	        total_value_to_current_index: 0,
-	        array_total_count: 0,
-	        count_at_this_value: 0,
-	        fresh_sub_bucket: true,
-	        visited_index: -1,
-	        current_iteration_value: HistogramIterationValue::new(),
+        state: IteratorSharedState::new(_histogram),
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for RecordedValuesIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
+
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        if self.state.total_count_to_current_index >= self.state.array_total_count {
+            return None;
         }
+    	Some(self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, histogram: &Histogram<T, S>| {
+	        let current_count = histogram.get_count_at_index(iterator_state.current_index);
+    	    (current_count != 0) && (iterator_state.visited_index != iterator_state.current_index)
+    	}))
     }
 }
 
-impl<'a> RecordedValuesIterator<'a> {
-    pub fn has_next(&mut self) -> bool {
-        self.state.total_count_to_current_index < self.state.array_total_count
+/// Steps down through the indexes at which values were recorded, starting from the index of the
+/// highest recorded value.
+///
+/// The total count and total value of each iteration value accumulate from the top, so they hold
+/// the number and sum of the values at or above `value_iterated_to`, and `value_iterated_from` is
+/// the value of the previous, higher step. `percentile` remains the percentile of values at or
+/// below `value_iterated_to`.
+pub struct RecordedValuesReverseIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
+    current_index: i32,
+    prev_value_iterated_to: i64,
+    total_count_from_top: i64,
+    total_value_from_top: i64,
+}
+
+pub fn new_reverse_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>) -> RecordedValuesReverseIterator<'a, T, S> {
+    let max_value = _histogram.get_max_value();
+    RecordedValuesReverseIterator {
+        histogram: _histogram,
+        current_index: cmp::min(_histogram.counts_array_index(max_value), _histogram.get_counts_array_length() - 1),
+        prev_value_iterated_to: _histogram.highest_equivalent_value(max_value),
+        total_count_from_top: 0,
+        total_value_from_top: 0,
     }
-    
-    pub fn reset(&mut self, total_count: i64, unit_magnitude: i32) {
-        self.state.reset(total_count, unit_magnitude);
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for RecordedValuesReverseIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
+
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        let total_count = self.histogram.get_total_count();
+        while self.current_index >= 0 && self.total_count_from_top < total_count {
+            let count = self.histogram.get_count_at_index(self.current_index);
+            let value_iterated_to = self.histogram.highest_equivalent_value(self.histogram.value_from_index(self.current_index));
+            self.current_index -= 1;
+            if count == 0 {
+                continue;
+            }
+
+            self.total_count_from_top += count;
+            self.total_value_from_top += count * value_iterated_to;
+            let percentile = (100.0f64 * (total_count - self.total_count_from_top + count) as f64) / total_count as f64;
+            let iteration_value = HistogramIterationValue {
+                value_iterated_to,
+                value_iterated_from: self.prev_value_iterated_to,
+                count_at_value_iterated_to: count,
+                count_added_in_this_iteration_step: count,
+                total_count_to_this_value: self.total_count_from_top,
+                total_value_to_this_value: self.total_value_from_top,
+                percentile,
+                percentile_level_iterated_to: percentile,
+            };
+            self.prev_value_iterated_to = value_iterated_to;
+            return Some(iteration_value);
+        }
+        None
     }
+}
 
-    pub fn next(&mut self) -> &HistogramIterationValue {
-    	self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, histogram: &Histogram| {
-	        let current_count = histogram.get_count_at_index(iterator_state.current_index);
-    	    (current_count != 0) && (iterator_state.visited_index != iterator_state.current_index)
-    	})
+/// Steps through the recorded values by percentile level, halving the distance to 100% every
+/// `percentile_ticks_per_half_distance` steps, and finishing with a step to the 100th percentile.
+pub struct PercentileIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
+    state: IteratorSharedState,
+    percentile_ticks_per_half_distance: i32,
+    percentile_level_to_iterate_to: f64,
+    reached_last_recorded_value: bool,
+}
+
+pub fn new_percentile_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>,
+                                                                 _percentile_ticks_per_half_distance: i32)
+                                                                 -> PercentileIterator<'a, T, S> {
+    assert!(_percentile_ticks_per_half_distance > 0, "percentile ticks per half distance must be positive");
+    PercentileIterator {
+        histogram: _histogram,
+        state: IteratorSharedState::new(_histogram),
+        percentile_ticks_per_half_distance: _percentile_ticks_per_half_distance,
+        percentile_level_to_iterate_to: 0.0,
+        reached_last_recorded_value: false,
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> PercentileIterator<'a, T, S> {
+    fn has_next(&mut self) -> bool {
+        if self.state.total_count_to_current_index < self.state.array_total_count {
+            return true;
+        }
+        // one additional last step to 100%
+        if !self.reached_last_recorded_value && self.state.array_total_count > 0 {
+            self.percentile_level_to_iterate_to = 100.0;
+            self.reached_last_recorded_value = true;
+            return true;
+        }
+        false
+    }
+
+    fn increment_iteration_level(&mut self) {
+        if self.reached_last_recorded_value {
+            return;
+        }
+        let half_distances = (100.0 / (100.0 - self.percentile_level_to_iterate_to)).log2().floor();
+        let percentile_reporting_ticks = self.percentile_ticks_per_half_distance as f64 * 2f64.powf(half_distances + 1.0);
+        self.percentile_level_to_iterate_to += 100.0 / percentile_reporting_ticks;
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for PercentileIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
+
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        if !self.has_next() {
+            return None;
+        }
+        let percentile_level_to_iterate_to = self.percentile_level_to_iterate_to;
+        let mut iteration_value =
+            self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, _histogram: &Histogram<T, S>| {
+                iterator_state.count_at_this_value != 0 &&
+                iterator_state.get_percentile_iterated_to() >= percentile_level_to_iterate_to
+            });
+        iteration_value.percentile_level_iterated_to = percentile_level_to_iterate_to;
+        self.increment_iteration_level();
+        Some(iteration_value)
+    }
+}
+
+/// Steps through the value range in buckets of `value_units_per_bucket`, up to the bucket
+/// containing the highest recorded value, including buckets in which nothing was recorded.
+pub struct LinearBucketValuesIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
+    state: IteratorSharedState,
+    value_units_per_bucket: i64,
+    next_value_reporting_level: i64,
+    next_value_reporting_level_lowest_equivalent: i64,
+}
+
+pub fn new_linear_bucket_values_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>,
+                                                                           _value_units_per_bucket: i64)
+                                                                           -> LinearBucketValuesIterator<'a, T, S> {
+    assert!(_value_units_per_bucket > 0, "value units per bucket must be positive");
+    LinearBucketValuesIterator {
+        histogram: _histogram,
+        state: IteratorSharedState::new(_histogram),
+        value_units_per_bucket: _value_units_per_bucket,
+        next_value_reporting_level: _value_units_per_bucket,
+        next_value_reporting_level_lowest_equivalent: _histogram.lowest_equivalent_value(_value_units_per_bucket),
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> LinearBucketValuesIterator<'a, T, S> {
+    fn has_next(&self) -> bool {
+        if self.state.total_count_to_current_index < self.state.array_total_count {
+            return true;
+        }
+        // keep stepping while the next step starts within the last visited sub-bucket, so that
+        // iteration ends on the step covering the highest recorded value
+        self.next_value_reporting_level_lowest_equivalent < self.state.next_value_at_index
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for LinearBucketValuesIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
+
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        if !self.has_next() {
+            return None;
+        }
+        let next_value_reporting_level_lowest_equivalent = self.next_value_reporting_level_lowest_equivalent;
+        let mut iteration_value =
+            self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, histogram: &Histogram<T, S>| {
+                iterator_state.current_value_at_index >= next_value_reporting_level_lowest_equivalent ||
+                iterator_state.current_index >= histogram.get_counts_array_length() - 1
+            });
+        iteration_value.value_iterated_to = self.next_value_reporting_level;
+        self.state.prev_value_iterated_to = self.next_value_reporting_level;
+        self.next_value_reporting_level += self.value_units_per_bucket;
+        self.next_value_reporting_level_lowest_equivalent =
+            self.histogram.lowest_equivalent_value(self.next_value_reporting_level);
+        Some(iteration_value)
+    }
+}
+
+/// Steps through the value range in buckets whose upper bounds start at
+/// `value_units_in_first_bucket` and grow by a factor of `log_base`, up to the bucket containing
+/// the highest recorded value, including buckets in which nothing was recorded.
+pub struct LogarithmicBucketValuesIterator<'a, T: Counter + 'a = u64, S: CountsArray<T> + 'a = Vec<T>> {
+    histogram: &'a Histogram<T, S>,
+    state: IteratorSharedState,
+    log_base: f64,
+    next_value_reporting_level: f64,
+    next_value_reporting_level_lowest_equivalent: i64,
+}
+
+pub fn new_logarithmic_bucket_values_iterator<'a, T: Counter, S: CountsArray<T>>(_histogram: &'a Histogram<T, S>,
+                                                                                _value_units_in_first_bucket: i64,
+                                                                                _log_base: f64)
+                                                                                -> LogarithmicBucketValuesIterator<'a, T, S> {
+    assert!(_value_units_in_first_bucket > 0, "value units in first bucket must be positive");
+    assert!(_log_base > 1.0, "log base must be greater than 1");
+    LogarithmicBucketValuesIterator {
+        histogram: _histogram,
+        state: IteratorSharedState::new(_histogram),
+        log_base: _log_base,
+        next_value_reporting_level: _value_units_in_first_bucket as f64,
+        next_value_reporting_level_lowest_equivalent: _histogram.lowest_equivalent_value(_value_units_in_first_bucket),
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> LogarithmicBucketValuesIterator<'a, T, S> {
+    fn has_next(&self) -> bool {
+        if self.state.total_count_to_current_index < self.state.array_total_count {
+            return true;
+        }
+        // keep stepping while the next step starts within the last visited sub-bucket, so that
+        // iteration ends on the step covering the highest recorded value
+        self.next_value_reporting_level_lowest_equivalent < self.state.next_value_at_index
+    }
+}
+
+impl<'a, T: Counter, S: CountsArray<T>> Iterator for LogarithmicBucketValuesIterator<'a, T, S> {
+    type Item = HistogramIterationValue;
+
+    fn next(&mut self) -> Option<HistogramIterationValue> {
+        if !self.has_next() {
+            return None;
+        }
+        let next_value_reporting_level_lowest_equivalent = self.next_value_reporting_level_lowest_equivalent;
+        let mut iteration_value =
+            self.state.next(self.histogram, |iterator_state: &mut IteratorSharedState, histogram: &Histogram<T, S>| {
+                iterator_state.current_value_at_index >= next_value_reporting_level_lowest_equivalent ||
+                iterator_state.current_index >= histogram.get_counts_array_length() - 1
+            });
+        let value_iterated_to = self.next_value_reporting_level as i64;
+        iteration_value.value_iterated_to = value_iterated_to;
+        self.state.prev_value_iterated_to = value_iterated_to;
+        self.next_value_reporting_level *= self.log_base;
+        self.next_value_reporting_level_lowest_equivalent =
+            self.histogram.lowest_equivalent_value(self.next_value_reporting_level as i64);
+        Some(iteration_value)
     }
 }
 
 impl fmt::Display for HistogramIterationValue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        try!(write!(f, "Value["));
-        try!(write!(f, "value_iterated_to: {}, ", self.value_iterated_to));
-        try!(write!(f, "value_iterated_from: {}, ", self.value_iterated_from));
-        try!(write!(f,
+        write!(f, "Value[")?;
+        write!(f, "value_iterated_to: {}, ", self.value_iterated_to)?;
+        write!(f, "value_iterated_from: {}, ", self.value_iterated_from)?;
+        write!(f,
                     "count_at_value_iterated_to: {}, ",
-                    self.count_at_value_iterated_to));
-        try!(write!(f,
+                    self.count_at_value_iterated_to)?;
+        write!(f,
                     "count_added_in_this_iteration_step: {}, ",
-                    self.count_added_in_this_iteration_step));
-        try!(write!(f,
+                    self.count_added_in_this_iteration_step)?;
+        write!(f,
                     "total_count_to_this_value: {}, ",
-                    self.total_count_to_this_value));
-        try!(write!(f,
+                    self.total_count_to_this_value)?;
+        write!(f,
                     "total_value_to_this_value: {}, ",
-                    self.total_value_to_this_value));
-        try!(write!(f, "percentile: {}, ", self.percentile));
-        try!(write!(f,
+                    self.total_value_to_this_value)?;
+        write!(f, "percentile: {}, ", self.percentile)?;
+        write!(f,
                     "percentile_level_iterated_to: {}, ",
-                    self.percentile_level_iterated_to));
+                    self.percentile_level_iterated_to)?;
 
         write!(f, "]")
     }
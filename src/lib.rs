@@ -1,3 +1,10 @@
 pub mod histogram;
 pub mod iter;
-pub mod encoding;
\ No newline at end of file
+pub mod encoding;
+pub mod counter;
+pub mod atomic;
+pub mod phaser;
+pub mod concurrent;
+pub mod recorder;
+pub mod double;
+pub mod packed;
\ No newline at end of file
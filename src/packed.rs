@@ -0,0 +1,96 @@
+use counter::*;
+use histogram::*;
+
+/// A `Histogram` whose counts are stored sparsely, so that its memory footprint grows with the
+/// number of populated indexes rather than with the covered value range.
+///
+/// A packed histogram has the same API and serialised form as `Histogram`, but recording a value
+/// at a previously unpopulated index is more expensive.
+pub type PackedHistogram<T = u64> = Histogram<T, PackedCounts<T>>;
+
+pub fn new_packed_histogram(_highest_trackable_value: i64, _number_of_significant_digits: i32) -> PackedHistogram {
+    new_packed_histogram_lower_bound(1, _highest_trackable_value, _number_of_significant_digits)
+}
+
+pub fn new_packed_histogram_lower_bound(_lowest_discernible_value: i64,
+                                        _highest_trackable_value: i64,
+                                        _number_of_significant_digits: i32)
+                                        -> PackedHistogram {
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits)
+        .lowest_discernible_value(_lowest_discernible_value);
+    match config.build_with_storage() {
+        Ok(histogram) => histogram,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+pub fn deserialise_packed_histogram(byte_array: &[u8], offset: i32) -> Option<PackedHistogram> {
+    deserialise_histogram_with_storage(byte_array, offset)
+}
+
+/// A counts array that only stores non-zero counts, ordered by index.
+pub struct PackedCounts<T: Counter> {
+    length: usize,
+    indexes: Vec<u32>,
+    counts: Vec<T>,
+}
+
+impl<T: Counter> PackedCounts<T> {
+    /// Returns the number of indexes currently holding a non-zero count.
+    pub fn get_populated_length(&self) -> usize {
+        self.indexes.len()
+    }
+}
+
+impl<T: Counter> CountsArray<T> for PackedCounts<T> {
+    fn with_length(length: usize) -> PackedCounts<T> {
+        PackedCounts {
+            length,
+            indexes: Vec::new(),
+            counts: Vec::new(),
+        }
+    }
+
+    fn length(&self) -> usize {
+        self.length
+    }
+
+    fn get(&self, index: usize) -> T {
+        assert!(index < self.length, "index {} out of bounds for counts array of length {}", index, self.length);
+        match self.indexes.binary_search(&(index as u32)) {
+            Ok(position) => self.counts[position],
+            Err(_) => T::zero(),
+        }
+    }
+
+    fn set(&mut self, index: usize, count: T) {
+        assert!(index < self.length, "index {} out of bounds for counts array of length {}", index, self.length);
+        let is_zero = count == T::zero();
+        match self.indexes.binary_search(&(index as u32)) {
+            Ok(position) if is_zero => {
+                self.indexes.remove(position);
+                self.counts.remove(position);
+            }
+            Ok(position) => self.counts[position] = count,
+            Err(_) if is_zero => {}
+            Err(position) => {
+                self.indexes.insert(position, index as u32);
+                self.counts.insert(position, count);
+            }
+        }
+    }
+
+    fn resize(&mut self, length: usize) {
+        let retained = match self.indexes.binary_search(&(length as u32)) {
+            Ok(position) | Err(position) => position,
+        };
+        self.indexes.truncate(retained);
+        self.counts.truncate(retained);
+        self.length = length;
+    }
+
+    fn clear(&mut self) {
+        self.indexes.clear();
+        self.counts.clear();
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+
+/// A port of HdrHistogram's `WriterReaderPhaser`.
+///
+/// Writers wrap their updates in a wait-free critical section. A reader holding the reader lock
+/// can call `flip_phase` to wait until all writers that entered their critical section before
+/// the flip have exited it; this allows a reader to swap out a data structure and then safely
+/// access the old one once no writer can still be using it.
+pub struct WriterReaderPhaser {
+    start_epoch: AtomicI64,
+    even_end_epoch: AtomicI64,
+    odd_end_epoch: AtomicI64,
+    reader_lock: Mutex<()>,
+}
+
+pub struct WriterCriticalSection<'a> {
+    phaser: &'a WriterReaderPhaser,
+    critical_value_at_enter: i64,
+}
+
+pub struct PhaserReaderGuard<'a> {
+    phaser: &'a WriterReaderPhaser,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl WriterReaderPhaser {
+    pub fn new() -> WriterReaderPhaser {
+        WriterReaderPhaser {
+            start_epoch: AtomicI64::new(0),
+            even_end_epoch: AtomicI64::new(0),
+            odd_end_epoch: AtomicI64::new(i64::MIN),
+            reader_lock: Mutex::new(()),
+        }
+    }
+
+    /// Enters a writer critical section, which is exited when the returned value is dropped.
+    pub fn writer_critical_section_enter(&self) -> WriterCriticalSection<'_> {
+        WriterCriticalSection {
+            phaser: self,
+            critical_value_at_enter: self.start_epoch.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+
+    /// Acquires the reader lock, which is required to flip the phase. Only one reader may hold
+    /// the lock at a time; writers are never blocked by it.
+    pub fn reader_lock(&self) -> PhaserReaderGuard<'_> {
+        PhaserReaderGuard {
+            phaser: self,
+            _guard: match self.reader_lock.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            },
+        }
+    }
+
+    fn writer_critical_section_exit(&self, critical_value_at_enter: i64) {
+        if critical_value_at_enter < 0 {
+            self.odd_end_epoch.fetch_add(1, Ordering::SeqCst);
+        } else {
+            self.even_end_epoch.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn flip_phase(&self) {
+        let next_phase_is_even = self.start_epoch.load(Ordering::SeqCst) < 0;
+
+        let initial_start_value = if next_phase_is_even { 0 } else { i64::MIN };
+        if next_phase_is_even {
+            self.even_end_epoch.store(initial_start_value, Ordering::SeqCst);
+        } else {
+            self.odd_end_epoch.store(initial_start_value, Ordering::SeqCst);
+        }
+
+        let start_value_at_flip = self.start_epoch.swap(initial_start_value, Ordering::SeqCst);
+
+        loop {
+            let caught_up = if next_phase_is_even {
+                self.odd_end_epoch.load(Ordering::SeqCst) == start_value_at_flip
+            } else {
+                self.even_end_epoch.load(Ordering::SeqCst) == start_value_at_flip
+            };
+            if caught_up {
+                return;
+            }
+            thread::yield_now();
+        }
+    }
+}
+
+impl Default for WriterReaderPhaser {
+    fn default() -> WriterReaderPhaser {
+        WriterReaderPhaser::new()
+    }
+}
+
+impl<'a> Drop for WriterCriticalSection<'a> {
+    fn drop(&mut self) {
+        self.phaser.writer_critical_section_exit(self.critical_value_at_enter);
+    }
+}
+
+impl<'a> PhaserReaderGuard<'a> {
+    /// Waits until all writer critical sections entered before this call have been exited.
+    pub fn flip_phase(&self) {
+        self.phaser.flip_phase();
+    }
+}
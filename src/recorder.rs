@@ -0,0 +1,389 @@
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicI64, AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use atomic::*;
+use concurrent::*;
+use histogram::*;
+use phaser::*;
+
+pub fn new_recorder(_highest_trackable_value: i64, _number_of_significant_digits: i32) -> Recorder {
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits);
+    match Recorder::from_config(&config) {
+        Ok(recorder) => recorder,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+/// Records values from many threads into interval histograms.
+///
+/// Writers record into an active histogram, while `get_interval_histogram` swaps in an empty
+/// histogram and returns everything recorded since the previous call, without blocking writers.
+///
+/// The phaser only keeps writers apart from the reader, not from each other, so the active and
+/// inactive histograms cannot be plain `Histogram`s: any number of threads may be recording into
+/// the active one at once. They are `AtomicHistogram`s, whose counts are updated atomically but
+/// which add no synchronisation of their own, unless auto-resize is configured, in which case
+/// they are `ConcurrentHistogram`s so that the counts array can grow while writers record. The
+/// values are copied out into a plain `Histogram`, reset with `Histogram::reset` when recycled.
+/// To record into plain `Histogram`s from a single thread, use `SingleWriterRecorder`.
+pub struct Recorder {
+    config: HistogramConfig,
+    phaser: WriterReaderPhaser,
+    active: AtomicPtr<IntervalCounts>,
+    inactive: AtomicPtr<IntervalCounts>,
+    active_start_time: AtomicI64,
+}
+
+/// The histograms a `Recorder` swaps between.
+enum IntervalCounts {
+    Atomic(AtomicHistogram),
+    Concurrent(ConcurrentHistogram),
+}
+
+impl Recorder {
+    pub fn from_config(config: &HistogramConfig) -> Result<Recorder, CreationError> {
+        let active = Box::new(IntervalCounts::from_config(config)?);
+        let inactive = Box::new(IntervalCounts::from_config(config)?);
+
+        Ok(Recorder {
+            config: *config,
+            phaser: WriterReaderPhaser::new(),
+            active: AtomicPtr::new(Box::into_raw(active)),
+            inactive: AtomicPtr::new(Box::into_raw(inactive)),
+            active_start_time: AtomicI64::new(current_time_millis()),
+        })
+    }
+
+    pub fn record_value(&self, value: i64) {
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_expected_interval(&self,
+                                               value: i64,
+                                               expected_interval_between_value_samples: i64) {
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&self, value: i64) -> Result<(), RecordError> {
+        let _critical_section = self.phaser.writer_critical_section_enter();
+        unsafe { (*self.active.load(Ordering::Acquire)).try_record_value(value) }
+    }
+
+    pub fn try_record_value_with_count(&self, value: i64, count: i64) -> Result<(), RecordError> {
+        let _critical_section = self.phaser.writer_critical_section_enter();
+        unsafe { (*self.active.load(Ordering::Acquire)).try_record_value_with_count(value, count) }
+    }
+
+    pub fn try_record_value_with_expected_interval(&self,
+                                                   value: i64,
+                                                   expected_interval_between_value_samples: i64)
+                                                   -> Result<(), RecordError> {
+        let _critical_section = self.phaser.writer_critical_section_enter();
+        unsafe {
+            (*self.active.load(Ordering::Acquire))
+                .try_record_value_with_expected_interval(value, expected_interval_between_value_samples)
+        }
+    }
+
+    /// Returns the values recorded since the previous call (or since construction or `reset`),
+    /// with the start and end times of the interval set on the returned histogram.
+    ///
+    /// A histogram previously returned by this recorder can be passed as `recycle` to be reset
+    /// and reused instead of allocating a new one. Panics, before the interval is ended, if
+    /// `recycle` has a different lowest discernible value or number of significant digits.
+    pub fn get_interval_histogram(&self, recycle: Option<Histogram>) -> Histogram {
+        let mut interval_histogram = match recycle {
+            Some(histogram) => {
+                if histogram.get_lowest_discernible_value() != self.config.lowest_discernible_value ||
+                   histogram.get_number_of_significant_value_digits() != self.config.number_of_significant_digits {
+                    panic!("Cannot recycle a histogram with a different configuration");
+                }
+                histogram
+            }
+            None => {
+                match self.config.build() {
+                    Ok(histogram) => histogram,
+                    Err(e) => panic!("Invalid histogram configuration: {}", e),
+                }
+            }
+        };
+        let reader = self.phaser.reader_lock();
+        let (start_time, end_time) = self.swap_active(&reader);
+        unsafe {
+            (*self.inactive.load(Ordering::Acquire)).copy_into(&mut interval_histogram);
+        }
+        interval_histogram.set_start_time(start_time);
+        interval_histogram.set_end_time(end_time);
+        interval_histogram
+    }
+
+    /// Discards all values recorded since the previous interval.
+    pub fn reset(&self) {
+        let reader = self.phaser.reader_lock();
+        self.swap_active(&reader);
+        unsafe {
+            (*self.inactive.load(Ordering::Acquire)).reset();
+        }
+    }
+
+    /// Swaps an empty histogram into the active position, returning the start and end times of
+    /// the interval recorded by the histogram that is now inactive.
+    fn swap_active(&self, reader: &PhaserReaderGuard) -> (i64, i64) {
+        let inactive = self.inactive.load(Ordering::Acquire);
+        unsafe {
+            (*inactive).reset();
+        }
+        let now = current_time_millis();
+        let active = self.active.swap(inactive, Ordering::AcqRel);
+        self.inactive.store(active, Ordering::Release);
+        let start_time = self.active_start_time.swap(now, Ordering::Relaxed);
+        // wait until no writer can still be recording into the previously active histogram
+        reader.flip_phase();
+        (start_time, now)
+    }
+}
+
+impl IntervalCounts {
+    fn from_config(config: &HistogramConfig) -> Result<IntervalCounts, CreationError> {
+        if config.auto_resize {
+            Ok(IntervalCounts::Concurrent(ConcurrentHistogram::from_config(config)?))
+        } else {
+            Ok(IntervalCounts::Atomic(AtomicHistogram::from_config(config)?))
+        }
+    }
+
+    fn try_record_value(&self, value: i64) -> Result<(), RecordError> {
+        match *self {
+            IntervalCounts::Atomic(ref histogram) => histogram.try_record_value(value),
+            IntervalCounts::Concurrent(ref histogram) => histogram.try_record_value(value),
+        }
+    }
+
+    fn try_record_value_with_count(&self, value: i64, count: i64) -> Result<(), RecordError> {
+        match *self {
+            IntervalCounts::Atomic(ref histogram) => histogram.try_record_value_with_count(value, count),
+            IntervalCounts::Concurrent(ref histogram) => histogram.try_record_value_with_count(value, count),
+        }
+    }
+
+    fn try_record_value_with_expected_interval(&self,
+                                               value: i64,
+                                               expected_interval_between_value_samples: i64)
+                                               -> Result<(), RecordError> {
+        match *self {
+            IntervalCounts::Atomic(ref histogram) => {
+                histogram.try_record_value_with_expected_interval(value, expected_interval_between_value_samples)
+            }
+            IntervalCounts::Concurrent(ref histogram) => {
+                histogram.try_record_value_with_expected_interval(value, expected_interval_between_value_samples)
+            }
+        }
+    }
+
+    fn reset(&self) {
+        match *self {
+            IntervalCounts::Atomic(ref histogram) => histogram.reset(),
+            IntervalCounts::Concurrent(ref histogram) => histogram.reset(),
+        }
+    }
+
+    fn copy_into(&self, target: &mut Histogram) {
+        match *self {
+            IntervalCounts::Atomic(ref histogram) => histogram.copy_into(target),
+            IntervalCounts::Concurrent(ref histogram) => histogram.copy_into(target),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(*self.active.get_mut()));
+            drop(Box::from_raw(*self.inactive.get_mut()));
+        }
+    }
+}
+
+pub fn new_single_writer_recorder(_highest_trackable_value: i64,
+                                  _number_of_significant_digits: i32)
+                                  -> SingleWriterRecorder {
+    let config = HistogramConfig::new(_highest_trackable_value, _number_of_significant_digits);
+    match SingleWriterRecorder::from_config(&config) {
+        Ok(recorder) => recorder,
+        Err(e) => panic!("Invalid histogram configuration: {}", e),
+    }
+}
+
+/// Records values from a single thread into interval histograms.
+///
+/// Recording writes straight into a plain `Histogram`; the only synchronisation on the hot path
+/// is a pair of stores to the writer epoch, so recording is cheaper than with `Recorder`.
+/// A `SingleWriterRecorder` can be moved to the recording thread but not shared between threads;
+/// a reporting thread uses an `IntervalHistogramReader` obtained from `interval_reader`.
+pub struct SingleWriterRecorder {
+    state: Arc<SingleWriterState>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+/// Retrieves interval histograms from a `SingleWriterRecorder` on another thread.
+#[derive(Clone)]
+pub struct IntervalHistogramReader {
+    state: Arc<SingleWriterState>,
+}
+
+struct SingleWriterState {
+    config: HistogramConfig,
+    // odd while the writer is recording, even otherwise
+    writer_epoch: AtomicI64,
+    active: AtomicPtr<Histogram>,
+    reader_lock: Mutex<()>,
+}
+
+impl SingleWriterRecorder {
+    pub fn from_config(config: &HistogramConfig) -> Result<SingleWriterRecorder, CreationError> {
+        let mut active = config.build()?;
+        active.set_start_time(current_time_millis());
+
+        Ok(SingleWriterRecorder {
+            state: Arc::new(SingleWriterState {
+                config: *config,
+                writer_epoch: AtomicI64::new(0),
+                active: AtomicPtr::new(Box::into_raw(Box::new(active))),
+                reader_lock: Mutex::new(()),
+            }),
+            _not_sync: PhantomData,
+        })
+    }
+
+    pub fn record_value(&self, value: i64) {
+        if let Err(e) = self.try_record_value(value) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn record_value_with_expected_interval(&self,
+                                               value: i64,
+                                               expected_interval_between_value_samples: i64) {
+        if let Err(e) = self.try_record_value_with_expected_interval(value,
+                                                                     expected_interval_between_value_samples) {
+            panic!("Failed to record value: {}", e);
+        }
+    }
+
+    pub fn try_record_value(&self, value: i64) -> Result<(), RecordError> {
+        self.record(|histogram| histogram.try_record_value(value))
+    }
+
+    pub fn try_record_value_with_count(&self, value: i64, count: i64) -> Result<(), RecordError> {
+        self.record(|histogram| histogram.try_record_value_with_count(value, count))
+    }
+
+    pub fn try_record_value_with_expected_interval(&self,
+                                                   value: i64,
+                                                   expected_interval_between_value_samples: i64)
+                                                   -> Result<(), RecordError> {
+        self.record(|histogram| {
+            histogram.try_record_value_with_expected_interval(value, expected_interval_between_value_samples)
+        })
+    }
+
+    /// See `Recorder::get_interval_histogram`.
+    pub fn get_interval_histogram(&self, recycle: Option<Histogram>) -> Histogram {
+        self.state.get_interval_histogram(recycle)
+    }
+
+    pub fn reset(&self) {
+        self.state.get_interval_histogram(None);
+    }
+
+    /// Returns a handle through which another thread can retrieve interval histograms.
+    pub fn interval_reader(&self) -> IntervalHistogramReader {
+        IntervalHistogramReader { state: self.state.clone() }
+    }
+
+    fn record<F>(&self, record: F) -> Result<(), RecordError>
+        where F: FnOnce(&mut Histogram) -> Result<(), RecordError>
+    {
+        // only this thread modifies the epoch, so plain stores suffice to publish it
+        let epoch = self.state.writer_epoch.load(Ordering::Relaxed);
+        self.state.writer_epoch.store(epoch + 1, Ordering::SeqCst);
+        let result = record(unsafe { &mut *self.state.active.load(Ordering::SeqCst) });
+        self.state.writer_epoch.store(epoch + 2, Ordering::Release);
+        result
+    }
+}
+
+impl IntervalHistogramReader {
+    /// See `Recorder::get_interval_histogram`.
+    pub fn get_interval_histogram(&self, recycle: Option<Histogram>) -> Histogram {
+        self.state.get_interval_histogram(recycle)
+    }
+
+    pub fn reset(&self) {
+        self.state.get_interval_histogram(None);
+    }
+}
+
+impl SingleWriterState {
+    fn get_interval_histogram(&self, recycle: Option<Histogram>) -> Histogram {
+        let mut replacement = match recycle {
+            Some(histogram) => {
+                if histogram.get_lowest_discernible_value() != self.config.lowest_discernible_value ||
+                   histogram.get_number_of_significant_value_digits() != self.config.number_of_significant_digits {
+                    panic!("Cannot recycle a histogram with a different configuration");
+                }
+                histogram
+            }
+            None => {
+                match self.config.build() {
+                    Ok(histogram) => histogram,
+                    Err(e) => panic!("Invalid histogram configuration: {}", e),
+                }
+            }
+        };
+        replacement.reset();
+        replacement.set_auto_resize(self.config.auto_resize);
+
+        let _reader = match self.reader_lock.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let now = current_time_millis();
+        replacement.set_start_time(now);
+        let previous = self.active.swap(Box::into_raw(Box::new(replacement)), Ordering::SeqCst);
+        // wait for a recording that may have started before the swap
+        let epoch = self.writer_epoch.load(Ordering::SeqCst);
+        if epoch & 1 == 1 {
+            while self.writer_epoch.load(Ordering::Acquire) == epoch {
+                thread::yield_now();
+            }
+        }
+
+        let mut interval_histogram = unsafe { *Box::from_raw(previous) };
+        interval_histogram.set_end_time(now);
+        interval_histogram
+    }
+}
+
+impl Drop for SingleWriterState {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(*self.active.get_mut()));
+        }
+    }
+}
+
+fn current_time_millis() -> i64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as i64,
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,92 @@
+extern crate rustogram;
+const HIGHEST_TRACKABLE_VALUE: i64 = 3600 * 1000 * 1000;
+const NUMBER_OF_SIGNIFICANT_VALUE_DIGITS: i32 = 3;
+
+use std::sync::Arc;
+use std::thread;
+use rustogram::atomic::*;
+use rustogram::histogram::*;
+
+#[test]
+fn test_record_value() {
+    let histogram = new_atomic_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value(1_000);
+    histogram.record_value(1_000_000);
+
+    assert_eq!(2, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+    assert_eq!(1_000, histogram.get_min_value());
+    assert_eq!(1_000_000, histogram.get_max_value());
+}
+
+#[test]
+fn test_try_record_value_out_of_range() {
+    let histogram = new_atomic_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    assert_eq!(Err(RecordError::ValueOutOfRange(HIGHEST_TRACKABLE_VALUE)),
+               histogram.try_record_value(HIGHEST_TRACKABLE_VALUE));
+    assert_eq!(Err(RecordError::NegativeValue(-1)), histogram.try_record_value(-1));
+    assert_eq!(0, histogram.get_total_count());
+}
+
+#[test]
+fn test_auto_resize_is_rejected() {
+    let config = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS).auto_resize(true);
+
+    assert_eq!(Some(CreationError::AutoResizeNotSupported),
+               AtomicHistogram::from_config(&config).err());
+}
+
+#[test]
+fn test_concurrent_recording() {
+    let histogram = Arc::new(new_atomic_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS));
+
+    let handles: Vec<_> = (0..4)
+        .map(|thread_index| {
+            let histogram = histogram.clone();
+            thread::spawn(move || {
+                for i in 0..10_000 {
+                    histogram.record_value(1_000 + (i % 100) * (thread_index + 1));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(40_000, histogram.get_total_count());
+    assert_eq!(1_000, histogram.get_min_value());
+    assert_eq!(1_000 + 99 * 4, histogram.get_max_value());
+
+    let snapshot = histogram.snapshot();
+    assert_eq!(40_000, snapshot.get_total_count());
+    assert_eq!(400, snapshot.get_count_at_value(1_000));
+    assert!(snapshot.values_are_equivalent(1_000 + 99 * 4, snapshot.get_max_value()));
+}
+
+#[test]
+fn test_snapshot_and_reset() {
+    let histogram = new_atomic_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(1_000, 100);
+
+    let snapshot = histogram.snapshot();
+    histogram.reset();
+
+    assert_eq!(10, snapshot.get_total_count());
+    assert_eq!(100, snapshot.get_min_value());
+    assert_eq!(HIGHEST_TRACKABLE_VALUE, snapshot.get_highest_trackable_value());
+    assert_eq!(0, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(1_000));
+    assert_eq!(0, histogram.get_max_value());
+}
+
+#[test]
+fn test_record_value_with_non_positive_expected_interval() {
+    let histogram = new_atomic_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(1_000, 0);
+
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+}
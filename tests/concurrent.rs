@@ -0,0 +1,123 @@
+extern crate rustogram;
+const HIGHEST_TRACKABLE_VALUE: i64 = 3600 * 1000 * 1000;
+const NUMBER_OF_SIGNIFICANT_VALUE_DIGITS: i32 = 3;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use rustogram::concurrent::*;
+use rustogram::histogram::*;
+use rustogram::phaser::*;
+
+#[test]
+fn test_flip_phase_waits_for_writers() {
+    let phaser = Arc::new(WriterReaderPhaser::new());
+    let writer_exited = Arc::new(AtomicBool::new(false));
+
+    let critical_section = phaser.writer_critical_section_enter();
+    let reader = {
+        let phaser = phaser.clone();
+        let writer_exited = writer_exited.clone();
+        thread::spawn(move || {
+            phaser.reader_lock().flip_phase();
+            assert!(writer_exited.load(Ordering::SeqCst));
+        })
+    };
+
+    thread::sleep(Duration::from_millis(50));
+    writer_exited.store(true, Ordering::SeqCst);
+    drop(critical_section);
+
+    reader.join().unwrap();
+    // writers entering after the flip do not hold up subsequent flips
+    drop(phaser.writer_critical_section_enter());
+    phaser.reader_lock().flip_phase();
+}
+
+#[test]
+fn test_record_value() {
+    let histogram = new_concurrent_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value(1_000);
+    histogram.record_value(1_000_000);
+
+    assert_eq!(2, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+    assert_eq!(1_000, histogram.get_min_value());
+    assert_eq!(1_000_000, histogram.get_max_value());
+    assert_eq!(Err(RecordError::ValueOutOfRange(i64::MAX)), histogram.try_record_value(i64::MAX));
+}
+
+#[test]
+fn test_concurrent_recording_with_auto_resize() {
+    let config = HistogramConfig::new(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS).auto_resize(true);
+    let histogram = Arc::new(ConcurrentHistogram::from_config(&config).unwrap());
+
+    let handles: Vec<_> = (0..4)
+        .map(|thread_index| {
+            let histogram = histogram.clone();
+            thread::spawn(move || {
+                for i in 0..10_000 {
+                    histogram.record_value((i + 1) * 1_000 * (thread_index + 1));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(40_000, histogram.get_total_count());
+    assert!(histogram.get_highest_trackable_value() >= 40_000_000);
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+    assert_eq!(3, histogram.get_count_at_value(4_000));
+
+    let snapshot = histogram.snapshot();
+    assert_eq!(40_000, snapshot.get_total_count());
+    assert!(snapshot.values_are_equivalent(40_000_000, snapshot.get_max_value()));
+    assert_eq!(1_000, snapshot.get_min_value());
+}
+
+#[test]
+fn test_snapshot_while_recording() {
+    let histogram = Arc::new(new_concurrent_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS));
+    let writer = {
+        let histogram = histogram.clone();
+        thread::spawn(move || {
+            for _ in 0..100_000 {
+                histogram.record_value(1_000);
+            }
+        })
+    };
+
+    let mut last_total_count = 0;
+    while last_total_count < 100_000 {
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.get_total_count(), snapshot.get_count_at_value(1_000));
+        assert!(snapshot.get_total_count() >= last_total_count);
+        last_total_count = snapshot.get_total_count();
+    }
+    writer.join().unwrap();
+}
+
+#[test]
+fn test_reset() {
+    let histogram = new_concurrent_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(1_000, 100);
+
+    histogram.reset();
+
+    assert_eq!(0, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(1_000));
+    assert_eq!(0, histogram.snapshot().get_total_count());
+}
+
+#[test]
+fn test_record_value_with_non_positive_expected_interval() {
+    let histogram = new_concurrent_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(1_000, 0);
+
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+}
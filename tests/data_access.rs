@@ -52,7 +52,7 @@ fn test_get_std_deviation() {
         expected_square_deviation_sum += (value as f64 - expected_mean).powf(2f64);
         value += 10_000;
     }
-    let expected_std_dev = (expected_square_deviation_sum as f64 / 20_000f64).sqrt();
+    let expected_std_dev = (expected_square_deviation_sum / 20_000f64).sqrt();
 
     assert_float_eq(expected_raw_std_dev, raw_histogram.get_std_deviation(), expected_raw_std_dev * 0.001);
     assert_float_eq(expected_std_dev, histogram.get_std_deviation(), expected_std_dev * 0.001);
@@ -98,6 +98,39 @@ fn test_get_percentile_at_or_below_value() {
     assert_float_eq(100.0, histogram.get_percentile_at_or_below_value(100000000), 0.0001);
 }
 
+#[test]
+fn test_get_values_at_percentiles() {
+    let histogram = get_histogram();
+    let percentiles = [99.999, 0.0, 50.0, 90.0, 30.0, 100.0, 99.0, 75.0, 150.0];
+
+    let values = histogram.get_values_at_percentiles(&percentiles);
+
+    assert_eq!(percentiles.len(), values.len());
+    for (&percentile, &value) in percentiles.iter().zip(values.iter()) {
+        assert_eq!(histogram.get_value_at_percentile(percentile), value);
+    }
+
+    let empty_histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    assert_eq!(vec![0, 0], empty_histogram.get_values_at_percentiles(&[50.0, 100.0]));
+    assert!(histogram.get_values_at_percentiles(&[]).is_empty());
+}
+
+#[test]
+fn test_get_percentiles_at_values() {
+    let histogram = get_histogram();
+    let values = [100_000_000, 5_000, 0, 1_000, 50_000_000, HIGHEST_TRACKABLE_VALUE * 2, 5_000];
+
+    let percentiles = histogram.get_percentiles_at_values(&values);
+
+    assert_eq!(values.len(), percentiles.len());
+    for (&value, &percentile) in values.iter().zip(percentiles.iter()) {
+        assert_eq!(histogram.get_percentile_at_or_below_value(value), percentile);
+    }
+
+    let empty_histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    assert_eq!(vec![100.0, 100.0], empty_histogram.get_percentiles_at_values(&[1, 1_000]));
+}
+
 #[test]
 fn test_get_count_between_values() {
     let histogram = get_histogram();
@@ -118,51 +151,24 @@ fn test_get_count_at_value() {
 }
 
 #[test]
-fn test_collect_method_returns_same_as_callback_method_for_recorded_values() {
-	let mut value_recorder = ValueRecorder { elements: Vec::new() };
-	let histogram = get_histogram();
-	let histogram_asserter = |record: Option<(i64, &HistogramIterationValue, &mut ValueRecorder)>| {
-		if record.is_some() {
-			let index_and_value = record.unwrap();
-			let (_, value, counter) = index_and_value;
-			counter.add_element(value);
-		}
-	};
-	histogram.get_recorded_values(histogram_asserter, &mut value_recorder);
-	
-	let mut collected = Vec::new();
-	histogram.collect_recorded_values(&mut collected);
-	
-	assert_eq!(collected.len(), value_recorder.elements.len());
-	let mut index = 0;
-	for collected_value in collected {
-		assert!(collected_value == value_recorder.elements[index]);
-		index += 1;
-	}
-}
+fn test_iterators_support_std_adapters() {
+    let histogram = get_histogram();
 
-#[test]
-fn test_collect_method_returns_same_as_callback_method_for_all_values() {
-	let mut value_recorder = ValueRecorder { elements: Vec::new() };
-	let histogram = get_histogram();
-	let histogram_asserter = |record: Option<(i64, &HistogramIterationValue, &mut ValueRecorder)>| {
-		if record.is_some() {
-			let index_and_value = record.unwrap();
-			let (_, value, counter) = index_and_value;
-			counter.add_element(value);
-		}
-	};
-	histogram.get_all_values(histogram_asserter, &mut value_recorder);
-	
-	let mut collected = Vec::new();
-	histogram.collect_all_values(&mut collected);
-	
-	assert_eq!(collected.len(), value_recorder.elements.len());
-	let mut index = 0;
-	for collected_value in collected {
-		assert!(collected_value == value_recorder.elements[index]);
-		index += 1;
-	}
+    let mut recorded_count = 0;
+    for value in histogram.iter_recorded() {
+        assert!(value.get_count_at_value_iterated_to() > 0);
+        recorded_count += value.get_count_at_value_iterated_to();
+    }
+    assert_eq!(histogram.get_total_count(), recorded_count);
+
+    let populated: Vec<HistogramIterationValue> = histogram.iter_all()
+        .filter(|value| value.get_count_at_value_iterated_to() > 0)
+        .collect();
+    let recorded: Vec<HistogramIterationValue> = histogram.iter_recorded().collect();
+    assert_eq!(recorded.iter().map(|value| value.get_value_iterated_to()).collect::<Vec<_>>(),
+               populated.iter().map(|value| value.get_value_iterated_to()).collect::<Vec<_>>());
+    assert_eq!(histogram.get_counts_array_length() as usize, histogram.iter_all().count());
+    assert_eq!(recorded[0], histogram.iter_recorded().next().unwrap());
 }
 
 #[test]
@@ -262,9 +268,157 @@ fn test_get_all_values() {
 	assert_eq!(20_000, total_added_counts);
 }
 
+#[test]
+fn test_collect_recorded_values_of_auto_resized_histogram() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_auto_resize(true);
+    histogram.record_value(1_000);
+    histogram.record_value(100_000_000);
+
+    let mut recorded = Vec::new();
+    histogram.collect_recorded_values(&mut recorded);
+    let mut all = Vec::new();
+    histogram.collect_all_values(&mut all);
+
+    assert_eq!(2, recorded.len());
+    assert!(histogram.values_are_equivalent(1_000, recorded[0].get_value_iterated_to()));
+    assert!(histogram.values_are_equivalent(100_000_000, recorded[1].get_value_iterated_to()));
+    assert_eq!(2, all.last().unwrap().get_total_count_to_this_value());
+}
+
+#[test]
+fn test_reverse_recorded_values() {
+    let histogram = get_histogram();
+
+    let mut forward: Vec<HistogramIterationValue> = histogram.iter_recorded().collect();
+    forward.reverse();
+    let reverse: Vec<HistogramIterationValue> = histogram.iter_recorded_rev().collect();
+
+    assert_eq!(forward.len(), reverse.len());
+    let mut total_count_from_top = 0;
+    for (forward_value, reverse_value) in forward.iter().zip(reverse.iter()) {
+        assert_eq!(forward_value.get_value_iterated_to(), reverse_value.get_value_iterated_to());
+        assert_eq!(forward_value.get_count_at_value_iterated_to(), reverse_value.get_count_at_value_iterated_to());
+        assert_eq!(forward_value.get_percentile(), reverse_value.get_percentile());
+        total_count_from_top += reverse_value.get_count_at_value_iterated_to();
+        assert_eq!(total_count_from_top, reverse_value.get_total_count_to_this_value());
+    }
+    assert!(histogram.values_are_equivalent(100_000_000, reverse[0].get_value_iterated_to()));
+    assert_eq!(reverse[0].get_value_iterated_to(), reverse[1].get_value_iterated_from());
+
+    // the buckets holding the worst 3 samples
+    let raw_histogram = get_raw_histogram();
+    let worst: Vec<HistogramIterationValue> = raw_histogram.iter_recorded_rev()
+        .take_while(|value| value.get_total_count_to_this_value() - value.get_count_at_value_iterated_to() < 3)
+        .collect();
+    assert_eq!(2, worst.len());
+    assert!(raw_histogram.values_are_equivalent(100_000_000, worst[0].get_value_iterated_to()));
+    assert!(raw_histogram.values_are_equivalent(1_000, worst[1].get_value_iterated_to()));
+    assert_eq!(10_001, worst[1].get_total_count_to_this_value());
+
+    let empty_histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    assert_eq!(None, empty_histogram.iter_recorded_rev().next());
+}
+
+#[test]
+fn test_percentile_values() {
+    let histogram = get_histogram();
+
+    let percentiles: Vec<HistogramIterationValue> = histogram.percentiles(5).collect();
+
+    for value in &percentiles {
+        assert_eq!(histogram.get_percentile_at_or_below_value(value.get_value_iterated_to()), value.get_percentile());
+        assert!(value.get_percentile() >= value.get_percentile_level_iterated_to());
+        if value.get_value_iterated_from() != value.get_value_iterated_to() && value.get_percentile_level_iterated_to() > 0.0 {
+            assert!(histogram.get_percentile_at_or_below_value(value.get_value_iterated_from()) <
+                    value.get_percentile_level_iterated_to());
+        }
+    }
+    let levels: Vec<f64> = percentiles.iter().take(8).map(|value| value.get_percentile_level_iterated_to()).collect();
+    assert_eq!(vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 55.0, 60.0], levels);
+    let last = percentiles.last().unwrap();
+    assert_eq!(100.0, last.get_percentile_level_iterated_to());
+    assert_eq!(histogram.get_total_count(), last.get_total_count_to_this_value());
+    assert!(histogram.values_are_equivalent(100_000_000, last.get_value_iterated_to()));
+}
+
+#[test]
+fn test_percentile_iterator_of_empty_histogram() {
+    let histogram = get_raw_histogram();
+    assert_eq!(histogram.percentiles(1).count(), new_percentile_iterator(&histogram, 1).count());
+
+    let empty_histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    assert_eq!(None, empty_histogram.percentiles(1).next());
+}
+
+#[test]
+#[should_panic(expected = "percentile ticks per half distance must be positive")]
+fn test_percentile_iterator_rejects_non_positive_ticks() {
+    get_histogram().percentiles(0);
+}
+
+#[test]
+fn test_linear_bucket_values() {
+    let histogram = get_histogram();
+    let raw_histogram = get_raw_histogram();
+
+    let raw_buckets: Vec<HistogramIterationValue> = raw_histogram.linear_bucket_values(100_000).collect();
+
+    assert_eq!(1_000, raw_buckets.len());
+    for (index, bucket) in raw_buckets.iter().enumerate() {
+        let expected_count = match index {
+            0 => 10_000,
+            999 => 1,
+            _ => 0,
+        };
+        assert_eq!(expected_count, bucket.get_count_added_in_this_iteration_step());
+        assert_eq!((index as i64 + 1) * 100_000, bucket.get_value_iterated_to());
+    }
+    assert_eq!(100_000, raw_buckets[1].get_value_iterated_from());
+
+    let buckets: Vec<HistogramIterationValue> = histogram.linear_bucket_values(10_000).collect();
+
+    // with 3 significant digits several linear buckets can share one equivalent value range, so
+    // the counts are only checked in total
+    assert_eq!(10_000, buckets.len());
+    assert_eq!(10_001, buckets[0].get_count_added_in_this_iteration_step());
+    assert_eq!(20_000, buckets.iter().map(|bucket| bucket.get_count_added_in_this_iteration_step()).sum::<i64>());
+}
+
+#[test]
+fn test_logarithmic_bucket_values() {
+    let histogram = get_histogram();
+    let raw_histogram = get_raw_histogram();
+
+    let mut index = 0;
+    for bucket in raw_histogram.logarithmic_bucket_values(10_000, 2.0) {
+        let expected_count = match index {
+            0 => 10_000,
+            14 => 1,
+            _ => 0,
+        };
+        assert_eq!(expected_count, bucket.get_count_added_in_this_iteration_step());
+        assert_eq!(10_000 << index, bucket.get_value_iterated_to());
+        index += 1;
+    }
+    assert_eq!(15, index);
+
+    let mut index = 0;
+    let mut total_added_count = 0;
+    for bucket in histogram.logarithmic_bucket_values(10_000, 2.0) {
+        if index == 0 {
+            assert_eq!(10_001, bucket.get_count_added_in_this_iteration_step());
+        }
+        total_added_count += bucket.get_count_added_in_this_iteration_step();
+        index += 1;
+    }
+    assert_eq!(15, index);
+    assert_eq!(20_000, total_added_count);
+}
+
 fn assert_float_eq(expected: f64, actual: f64, delta: f64) {
     if !(actual > expected - delta && actual < expected + delta) {
-        panic!(format!("Expected {} to be equal to {} +/-{}", actual, expected, delta));
+        panic!("Expected {} to be equal to {} +/-{}", actual, expected, delta);
     }
 }
 
@@ -291,15 +445,3 @@ fn get_raw_histogram() -> Histogram {
     h.record_value(100_000_000);
     h
 }
-
-struct ValueRecorder {
-	elements: Vec<HistogramIterationValue>
-}
-
-impl ValueRecorder {
-	fn add_element(&mut self, value: &HistogramIterationValue) {
-		let mut copy = HistogramIterationValue::new();
-		value.copy_to(&mut copy);
-		self.elements.push(copy);
-	}
-}
\ No newline at end of file
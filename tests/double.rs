@@ -0,0 +1,111 @@
+extern crate rustogram;
+const HIGHEST_TO_LOWEST_VALUE_RATIO: i64 = 1_000_000_000_000;
+const NUMBER_OF_SIGNIFICANT_VALUE_DIGITS: i32 = 3;
+
+use rustogram::double::*;
+use rustogram::histogram::*;
+
+#[test]
+fn test_record_value() {
+    let mut histogram = new_double_histogram(HIGHEST_TO_LOWEST_VALUE_RATIO, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value(0.5);
+    histogram.record_value(1.5);
+    histogram.record_value(0.0);
+
+    assert_eq!(3, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(0.5));
+    assert_eq!(1, histogram.get_count_at_value(0.0));
+    assert!(histogram.values_are_equivalent(0.5, histogram.get_min_value()));
+    assert!(histogram.values_are_equivalent(1.5, histogram.get_max_value()));
+    assert!(histogram.values_are_equivalent(1.5, histogram.get_value_at_percentile(100.0)));
+    assert!((histogram.get_mean() - 2.0 / 3.0).abs() < 0.001);
+}
+
+#[test]
+fn test_range_shifts_to_cover_recorded_values() {
+    let mut histogram = new_double_histogram(HIGHEST_TO_LOWEST_VALUE_RATIO, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value(1_000.0);
+    histogram.record_value(0.001);
+    histogram.record_value(1_000_000_000.0);
+
+    assert_eq!(3, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1_000.0));
+    assert_eq!(1, histogram.get_count_at_value(0.001));
+    assert_eq!(1, histogram.get_count_at_value(1_000_000_000.0));
+    assert!(histogram.get_current_lowest_value_in_auto_range() <= 0.001);
+    assert!(histogram.get_current_highest_value_limit_in_auto_range() > 1_000_000_000.0);
+    assert!(histogram.values_are_equivalent(1_000.0, histogram.get_value_at_percentile(50.0)));
+    assert!(histogram.values_are_equivalent(0.001, histogram.lowest_equivalent_value(0.001)));
+    assert!(histogram.highest_equivalent_value(1_000.0) < 1_001.0);
+}
+
+#[test]
+fn test_values_beyond_dynamic_range_are_rejected() {
+    let mut histogram = new_double_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(1.0);
+
+    assert_eq!(Err(DoubleRecordError::ValueOutOfRange(1_000_000.0)),
+               histogram.try_record_value(1_000_000.0));
+    assert_eq!(Err(DoubleRecordError::ValueOutOfRange(0.000_001)),
+               histogram.try_record_value(0.000_001));
+    assert_eq!(Err(DoubleRecordError::NegativeValue(-1.0)), histogram.try_record_value(-1.0));
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(1.0));
+}
+
+#[test]
+fn test_invalid_dynamic_range() {
+    assert_eq!(Some(CreationError::HighestToLowestValueRatioOutOfRange(1)),
+               DoubleHistogram::from_dynamic_range(1, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS).err());
+    assert_eq!(Some(CreationError::SignificantDigitsOutOfRange(6)),
+               DoubleHistogram::from_dynamic_range(HIGHEST_TO_LOWEST_VALUE_RATIO, 6).err());
+}
+
+#[test]
+fn test_dynamic_range_at_integer_range_limit() {
+    // the smallest ratios whose internal integer range would not fit in an i64
+    for &(ratio, number_of_significant_digits) in &[(1i64 << 57, 1), (1 << 54, 2), (1 << 47, 4), (1 << 44, 5)] {
+        let mut histogram = DoubleHistogram::from_dynamic_range(ratio - 1, number_of_significant_digits).unwrap();
+        histogram.record_value(1.0);
+        histogram.record_value((ratio / 2) as f64);
+        assert_eq!(2, histogram.get_total_count());
+
+        assert_eq!(Some(CreationError::HighestToLowestValueRatioOutOfRange(ratio)),
+                   DoubleHistogram::from_dynamic_range(ratio, number_of_significant_digits).err());
+        assert_eq!(Some(CreationError::HighestToLowestValueRatioOutOfRange(ratio + 1)),
+                   DoubleHistogram::from_dynamic_range(ratio + 1, number_of_significant_digits).err());
+    }
+}
+
+#[test]
+fn test_serialisation_round_trip() {
+    let mut histogram = new_double_histogram(HIGHEST_TO_LOWEST_VALUE_RATIO, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(12.5, 2.5);
+    histogram.record_value(0.000_25);
+
+    let mut buffer = Vec::new();
+    histogram.serialise(&mut buffer);
+    let deserialised = deserialise_double_histogram(&buffer, 0).unwrap();
+
+    assert_eq!(0x0c72124e, i32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]));
+    assert_eq!(HIGHEST_TO_LOWEST_VALUE_RATIO, deserialised.get_highest_to_lowest_value_ratio());
+    assert_eq!(histogram.get_total_count(), deserialised.get_total_count());
+    assert_eq!(histogram.get_integer_to_double_value_conversion_ratio(),
+               deserialised.get_integer_to_double_value_conversion_ratio());
+    assert_eq!(1, deserialised.get_count_at_value(2.5));
+    assert_eq!(1, deserialised.get_count_at_value(12.5));
+    assert_eq!(1, deserialised.get_count_at_value(0.000_25));
+    assert_eq!(histogram.get_mean(), deserialised.get_mean());
+    assert_eq!(None, deserialise_histogram(&buffer, 0).map(|h| h.get_total_count()));
+}
+
+#[test]
+fn test_record_value_with_non_positive_expected_interval() {
+    let mut histogram = new_double_histogram(HIGHEST_TO_LOWEST_VALUE_RATIO, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value_with_expected_interval(12.5, 0.0);
+
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(12.5));
+}
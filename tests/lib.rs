@@ -63,6 +63,20 @@ fn test_reset() {
     verify_max_value(histogram);
 }
 
+#[test]
+fn test_reset_clears_metadata() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_start_time(1_000);
+    histogram.set_end_time(2_000);
+    histogram.set_tag(Some("tag".to_string()));
+
+    histogram.reset();
+
+    assert_eq!(0, histogram.get_start_time());
+    assert_eq!(0, histogram.get_end_time());
+    assert_eq!(None, histogram.get_tag());
+}
+
 #[test]
 fn test_get_min_value() {
     let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
@@ -82,6 +96,588 @@ fn test_get_max_value() {
     assert_eq!(1_000_000, histogram.get_max_value());
 }
 
+#[test]
+fn test_auto_resize_grows_to_cover_recorded_value() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_auto_resize(true);
+
+    histogram.record_value(TEST_VALUE_LEVEL);
+    histogram.record_value(HIGHEST_TRACKABLE_VALUE);
+
+    assert!(histogram.get_highest_trackable_value() >= HIGHEST_TRACKABLE_VALUE);
+    assert_eq!(2, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+    assert!(histogram.values_are_equivalent(HIGHEST_TRACKABLE_VALUE, histogram.get_max_value()));
+}
+
+#[test]
+fn test_auto_resize_to_cover_i64_max() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_auto_resize(true);
+
+    histogram.record_value(i64::MAX);
+
+    assert_eq!(i64::MAX, histogram.get_highest_trackable_value());
+    assert_eq!(1, histogram.get_count_at_value(i64::MAX));
+    assert_eq!(i64::MAX, histogram.get_max_value());
+    assert_eq!(i64::MAX, histogram.get_value_at_percentile(100.0));
+}
+
+#[test]
+fn test_try_record_value_into_top_bucket() {
+    for number_of_significant_value_digits in 0..6 {
+        let mut histogram = new_histogram(1_000, number_of_significant_value_digits);
+        assert_eq!(Err(RecordError::ValueOutOfRange(i64::MAX)), histogram.try_record_value(i64::MAX));
+
+        histogram.set_auto_resize(true);
+        assert_eq!(Ok(()), histogram.try_record_value(i64::MAX));
+        assert_eq!(Ok(()), histogram.try_record_value(i64::MAX / 2 + 1));
+        assert_eq!(Ok(()), histogram.try_record_values(&[i64::MAX]));
+        assert_eq!(3, histogram.get_total_count());
+    }
+}
+
+#[test]
+fn test_auto_resize_preserves_existing_counts() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_auto_resize(true);
+    histogram.record_value(500);
+    histogram.record_value(999);
+
+    histogram.record_value_with_expected_interval(1_000_000, 200_000);
+
+    assert_eq!(1, histogram.get_count_at_value(500));
+    assert_eq!(1, histogram.get_count_at_value(999));
+    assert_eq!(7, histogram.get_total_count());
+    assert!(histogram.values_are_equivalent(500, histogram.get_value_at_percentile(0.0)));
+    assert!(histogram.values_are_equivalent(1_000_000, histogram.get_value_at_percentile(100.0)));
+}
+
+#[test]
+fn test_try_record_value_out_of_range() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    assert_eq!(Err(RecordError::ValueOutOfRange(HIGHEST_TRACKABLE_VALUE)),
+               histogram.try_record_value(HIGHEST_TRACKABLE_VALUE));
+    assert_eq!(Ok(()), histogram.try_record_value(TEST_VALUE_LEVEL));
+
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(TEST_VALUE_LEVEL, histogram.get_max_value());
+}
+
+#[test]
+fn test_try_record_negative_value_or_count() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    assert_eq!(Err(RecordError::NegativeValue(-1)), histogram.try_record_value(-1));
+    assert_eq!(Err(RecordError::NegativeCount(-5)),
+               histogram.try_record_value_with_count(TEST_VALUE_LEVEL, -5));
+    assert_eq!(0, histogram.get_total_count());
+}
+
+#[test]
+fn test_try_record_value_with_count_overflow() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    assert_eq!(Ok(()), histogram.try_record_value_with_count(TEST_VALUE_LEVEL, i64::MAX));
+    assert_eq!(Err(RecordError::CountOverflow),
+               histogram.try_record_value_with_count(TEST_VALUE_LEVEL, 1));
+    assert_eq!(Err(RecordError::CountOverflow), histogram.try_record_value(1_000));
+
+    assert_eq!(i64::MAX, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(1_000));
+}
+
+#[test]
+fn test_try_record_value_with_expected_interval() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    assert_eq!(Err(RecordError::ValueOutOfRange(5_000)),
+               histogram.try_record_value_with_expected_interval(5_000, 100));
+    assert_eq!(Ok(()), histogram.try_record_value_with_expected_interval(500, 100));
+
+    assert_eq!(5, histogram.get_total_count());
+}
+
+#[test]
+fn test_record_value_with_non_positive_expected_interval() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value_with_expected_interval(1_000, 0);
+    histogram.record_value_with_count_and_expected_interval(1_000, 1, 0);
+    histogram.record_value_with_expected_interval(2_000, -100);
+
+    assert_eq!(3, histogram.get_total_count());
+    assert_eq!(2, histogram.get_count_at_value(1_000));
+    assert_eq!(1, histogram.get_count_at_value(2_000));
+}
+
+#[test]
+#[should_panic]
+fn test_record_value_out_of_range_panics() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value(HIGHEST_TRACKABLE_VALUE);
+}
+
+#[test]
+fn test_config_builds_histogram() {
+    let histogram = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .lowest_discernible_value(1_000)
+        .auto_resize(true)
+        .build()
+        .unwrap();
+
+    assert_eq!(1_000, histogram.get_lowest_discernible_value());
+    assert_eq!(HIGHEST_TRACKABLE_VALUE, histogram.get_highest_trackable_value());
+    assert_eq!(NUMBER_OF_SIGNIFICANT_VALUE_DIGITS, histogram.get_number_of_significant_value_digits());
+    assert!(histogram.is_auto_resize());
+}
+
+#[test]
+fn test_config_rejects_invalid_arguments() {
+    assert_eq!(Some(CreationError::LowestDiscernibleValueTooSmall(0)),
+               HistogramConfig::new(1_000, 3).lowest_discernible_value(0).build().err());
+    assert_eq!(Some(CreationError::LowestDiscernibleValueTooLarge(i64::MAX / 2 + 1)),
+               HistogramConfig::new(i64::MAX, 3).lowest_discernible_value(i64::MAX / 2 + 1).build().err());
+    assert_eq!(Some(CreationError::SignificantDigitsOutOfRange(6)),
+               HistogramConfig::new(1_000, 6).build().err());
+    assert_eq!(Some(CreationError::SignificantDigitsOutOfRange(-1)),
+               HistogramConfig::new(1_000, -1).build().err());
+    assert_eq!(Some(CreationError::HighestTrackableValueTooSmall(1_999)),
+               HistogramConfig::new(1_999, 3).lowest_discernible_value(1_000).build().err());
+    assert_eq!(Some(CreationError::CannotRepresentSignificantDigitsBeyondLowest),
+               HistogramConfig::new(i64::MAX, 5).lowest_discernible_value(1 << 50).build().err());
+}
+
+#[test]
+#[should_panic]
+fn test_new_histogram_panics_on_invalid_arguments() {
+    new_histogram_lower_bound(0, HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+}
+
+#[test]
+fn test_narrow_counter_detects_overflow() {
+    let mut histogram: Histogram<u16> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+
+    assert_eq!(Ok(()), histogram.try_record_value_with_count(TEST_VALUE_LEVEL, 65_535));
+    assert_eq!(Err(RecordError::CountOverflow), histogram.try_record_value(TEST_VALUE_LEVEL));
+
+    assert_eq!(65_535, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(65_535, histogram.get_total_count());
+}
+
+#[test]
+fn test_narrow_counter_records_values() {
+    let mut histogram: Histogram<u8> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+
+    histogram.record_value(1_000);
+    histogram.record_value(1_000_000);
+
+    assert_eq!(2, histogram.get_total_count());
+    assert_eq!(1_000, histogram.get_min_value());
+    assert_eq!(1_000_000, histogram.get_max_value());
+    assert!(histogram.values_are_equivalent(1_000, histogram.get_value_at_percentile(50.0)));
+}
+
+#[test]
+fn test_add() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000);
+    other.record_value(TEST_VALUE_LEVEL);
+    other.record_value(TEST_VALUE_LEVEL * 1_000_000);
+
+    assert_eq!(Ok(()), histogram.add(&other));
+
+    assert_eq!(4, histogram.get_total_count());
+    assert_eq!(2, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL * 1_000_000));
+    assert_eq!(TEST_VALUE_LEVEL, histogram.get_min_value());
+    assert_eq!(TEST_VALUE_LEVEL * 1_000_000, histogram.get_max_value());
+}
+
+#[test]
+fn test_add_with_different_configuration() {
+    let mut histogram = new_histogram_lower_bound(1_000, HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other: Histogram<u8> = HistogramConfig::new(1_000_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+    other.record_value(2_000);
+    other.try_record_value_with_count(500_000, 3).unwrap();
+
+    assert_eq!(Ok(()), histogram.add(&other));
+
+    assert_eq!(4, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(2_000));
+    assert_eq!(3, histogram.get_count_at_value(500_000));
+    assert!(histogram.values_are_equivalent(2_000, histogram.get_min_value()));
+    assert!(histogram.values_are_equivalent(500_000, histogram.get_max_value()));
+}
+
+#[test]
+fn test_add_out_of_range() {
+    let mut histogram = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    other.record_value(TEST_VALUE_LEVEL);
+    other.record_value(HIGHEST_TRACKABLE_VALUE);
+
+    assert_eq!(Err(RecordError::ValueOutOfRange(HIGHEST_TRACKABLE_VALUE)), histogram.add(&other));
+    assert_eq!(0, histogram.get_total_count());
+
+    histogram.set_auto_resize(true);
+    assert_eq!(Ok(()), histogram.add(&other));
+    assert_eq!(2, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+}
+
+#[test]
+fn test_add_overflow_leaves_histogram_unchanged() {
+    let mut histogram: Histogram<u8> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+    let mut other: Histogram<u8> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+    histogram.try_record_value_with_count(TEST_VALUE_LEVEL, 200).unwrap();
+    other.record_value(1);
+    other.try_record_value_with_count(TEST_VALUE_LEVEL, 100).unwrap();
+
+    assert_eq!(Err(RecordError::CountOverflow), histogram.add(&other));
+    assert_eq!(200, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(1));
+}
+
+#[test]
+fn test_subtract() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000);
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000_000);
+    other.record_value(TEST_VALUE_LEVEL);
+    other.record_value(TEST_VALUE_LEVEL * 1_000_000);
+
+    assert_eq!(Ok(()), histogram.subtract(&other));
+
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL * 1_000));
+    assert!(histogram.values_are_equivalent(TEST_VALUE_LEVEL * 1_000, histogram.get_min_value()));
+    assert!(histogram.values_are_equivalent(TEST_VALUE_LEVEL * 1_000, histogram.get_max_value()));
+
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000_000);
+    let mut added = new_histogram_lower_bound(1_000, HIGHEST_TRACKABLE_VALUE, 2);
+    added.record_value(TEST_VALUE_LEVEL * 1_000_000);
+    histogram.add(&added).unwrap();
+    assert_eq!(Ok(()), histogram.subtract(&added));
+    assert_eq!(2, histogram.get_total_count());
+}
+
+#[test]
+fn test_subtract_rejects_negative_counts() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    other.record_value(TEST_VALUE_LEVEL);
+    other.record_value(TEST_VALUE_LEVEL * 1_000);
+
+    assert_eq!(Err(SubtractionError::NegativeCount(TEST_VALUE_LEVEL * 1_000)), histogram.subtract(&other));
+    assert_eq!(1, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+
+    let mut wide = new_histogram(HIGHEST_TRACKABLE_VALUE * 1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    wide.record_value(HIGHEST_TRACKABLE_VALUE * 1_000);
+    assert_eq!(Err(SubtractionError::ValueOutOfRange(HIGHEST_TRACKABLE_VALUE * 1_000)), histogram.subtract(&wide));
+}
+
+#[test]
+fn test_shift_values_left() {
+    for &lowest_discernible_value in &[1, 1_000] {
+        let mut histogram = new_histogram_lower_bound(lowest_discernible_value, HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+        histogram.record_value(0);
+        histogram.record_value(2_000);
+        histogram.record_value(500_000);
+        histogram.record_value(1_000_000);
+
+        assert_eq!(Ok(()), histogram.shift_values_left(4));
+
+        assert_eq!(4, histogram.get_total_count());
+        assert_eq!(1, histogram.get_count_at_value(0));
+        let shifted_min_value = histogram.lowest_equivalent_value(2_000) << 4;
+        assert_eq!(1, histogram.get_count_at_value(shifted_min_value));
+        assert_eq!(1, histogram.get_count_at_value(histogram.lowest_equivalent_value(500_000) << 4));
+        assert_eq!(1, histogram.get_count_at_value(1_000_000 << 4));
+        assert_eq!(0, histogram.get_count_at_value(2_000));
+        assert!(histogram.values_are_equivalent(shifted_min_value, histogram.get_min_value()));
+        assert!(histogram.values_are_equivalent(1_000_000 << 4, histogram.get_max_value()));
+    }
+}
+
+#[test]
+fn test_shift_values_then_resize() {
+    let mut histogram = new_histogram(1_000_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.set_auto_resize(true);
+    histogram.record_value(3_000);
+    histogram.record_value(500_000);
+
+    assert_eq!(Ok(()), histogram.shift_values_left(1));
+    histogram.record_value(HIGHEST_TRACKABLE_VALUE);
+
+    assert_eq!(1, histogram.get_count_at_value(6_000));
+    assert_eq!(1, histogram.get_count_at_value(1_000_000));
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+
+    assert_eq!(Ok(()), histogram.shift_values_right(1));
+
+    assert_eq!(0, histogram.get_normalizing_index_offset());
+    assert_eq!(1, histogram.get_count_at_value(3_000));
+    assert_eq!(1, histogram.get_count_at_value(500_000));
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE / 2));
+    assert_eq!(3, histogram.get_total_count());
+}
+
+#[test]
+fn test_shift_values_left_overflow() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    histogram.record_value(HIGHEST_TRACKABLE_VALUE / 2);
+
+    assert_eq!(Ok(()), histogram.shift_values_left(1));
+    assert_eq!(Err(ShiftError::Overflow), histogram.shift_values_left(1));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL << 1));
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+}
+
+#[test]
+fn test_shift_values_right_underflow() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000);
+    histogram.record_value(TEST_VALUE_LEVEL * 1_000_000);
+
+    assert_eq!(Err(ShiftError::Underflow), histogram.shift_values_right(2));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL * 1_000));
+
+    assert_eq!(Ok(()), histogram.shift_values_right(1));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL * 500));
+    assert_eq!(1, histogram.get_count_at_value(TEST_VALUE_LEVEL * 500_000));
+    assert_eq!(TEST_VALUE_LEVEL * 500, histogram.get_min_value());
+    assert_eq!(TEST_VALUE_LEVEL * 500_000, histogram.get_max_value());
+}
+
+#[test]
+fn test_copy_corrected_for_coordinated_omission() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut corrected_at_record_time = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    for _ in 0..10 {
+        histogram.record_value(100);
+        corrected_at_record_time.record_value_with_expected_interval(100, 100);
+    }
+    histogram.record_value(2_000);
+    corrected_at_record_time.record_value_with_expected_interval(2_000, 100);
+
+    let corrected = histogram.copy_corrected_for_coordinated_omission(100);
+
+    assert_eq!(11, histogram.get_total_count());
+    assert_eq!(corrected_at_record_time.get_total_count(), corrected.get_total_count());
+    assert_eq!(30, corrected.get_total_count());
+    for value in (100..2_001).step_by(100) {
+        assert_eq!(corrected_at_record_time.get_count_at_value(value), corrected.get_count_at_value(value));
+    }
+    assert_eq!(100, corrected.get_min_value());
+    assert_eq!(2_000, corrected.get_max_value());
+    assert_eq!(11, histogram.copy_corrected_for_coordinated_omission(0).get_total_count());
+}
+
+#[test]
+fn test_add_while_correcting_for_coordinated_omission() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    other.try_record_value_with_count(1_000, 2).unwrap();
+
+    assert_eq!(Ok(()), histogram.add_while_correcting_for_coordinated_omission(&other, 250));
+
+    assert_eq!(9, histogram.get_total_count());
+    assert_eq!(2, histogram.get_count_at_value(250));
+    assert_eq!(2, histogram.get_count_at_value(1_000));
+    assert_eq!(TEST_VALUE_LEVEL, histogram.get_min_value());
+
+    let mut narrow = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    other.record_value(HIGHEST_TRACKABLE_VALUE);
+    assert_eq!(Err(RecordError::ValueOutOfRange(other.highest_equivalent_value(HIGHEST_TRACKABLE_VALUE))),
+               narrow.add_while_correcting_for_coordinated_omission(&other, 250));
+    assert_eq!(0, narrow.get_total_count());
+}
+
+#[test]
+fn test_record_value_with_count() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_value_with_count(TEST_VALUE_LEVEL, 5);
+    histogram.record_value_with_count_and_expected_interval(1_000, 2, 250);
+
+    assert_eq!(13, histogram.get_total_count());
+    assert_eq!(5, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(2, histogram.get_count_at_value(250));
+    assert_eq!(2, histogram.get_count_at_value(1_000));
+    assert_eq!(TEST_VALUE_LEVEL, histogram.get_min_value());
+    assert_eq!(1_000, histogram.get_max_value());
+}
+
+#[test]
+fn test_record_values() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+
+    histogram.record_values(&[0, TEST_VALUE_LEVEL, 1_000, TEST_VALUE_LEVEL, 1_000_000]);
+    histogram.record_values(&[]);
+
+    assert_eq!(5, histogram.get_total_count());
+    assert_eq!(1, histogram.get_count_at_value(0));
+    assert_eq!(2, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(TEST_VALUE_LEVEL, histogram.get_min_value());
+    assert_eq!(1_000_000, histogram.get_max_value());
+
+    assert_eq!(Err(RecordError::ValueOutOfRange(i64::MAX)), histogram.try_record_values(&[1, i64::MAX]));
+    assert_eq!(Err(RecordError::NegativeValue(-1)), histogram.try_record_values(&[1, -1]));
+    assert_eq!(5, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(1));
+}
+
+#[test]
+fn test_record_values_overflow_records_nothing() {
+    let mut histogram: Histogram<u8> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+    histogram.record_value_with_count(TEST_VALUE_LEVEL, 254);
+
+    assert_eq!(Err(RecordError::CountOverflow), histogram.try_record_values(&[1, TEST_VALUE_LEVEL, TEST_VALUE_LEVEL]));
+
+    assert_eq!(254, histogram.get_total_count());
+    assert_eq!(254, histogram.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(0, histogram.get_count_at_value(1));
+}
+
+#[test]
+fn test_clone() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_values(&[TEST_VALUE_LEVEL, 1_000, 1_000_000]);
+    histogram.set_start_time(1_000);
+
+    let mut copy = histogram.clone();
+
+    assert!(copy == histogram);
+    assert_eq!(3, copy.get_total_count());
+    assert_eq!(1_000_000, copy.get_max_value());
+    assert_eq!(1_000, copy.get_start_time());
+
+    copy.record_value(1_000);
+    assert!(copy != histogram);
+    assert_eq!(1, histogram.get_count_at_value(1_000));
+}
+
+#[test]
+fn test_copy_into() {
+    let mut histogram = new_histogram_lower_bound(1_000, HIGHEST_TRACKABLE_VALUE, 2);
+    histogram.record_values(&[1_000, 1_000_000]);
+    let mut target = new_histogram(1_000, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    target.record_value(TEST_VALUE_LEVEL);
+
+    histogram.copy_into(&mut target);
+
+    assert!(target == histogram);
+    assert_eq!(1_000, target.get_lowest_discernible_value());
+    assert_eq!(2, target.get_number_of_significant_value_digits());
+    assert_eq!(histogram.get_counts_array_length(), target.get_counts_array_length());
+    assert_eq!(0, target.get_count_at_value(TEST_VALUE_LEVEL));
+    assert_eq!(1, target.get_count_at_value(1_000_000));
+}
+
+#[test]
+fn test_equality_ignores_counts_array_length() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut resized = new_histogram(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    resized.set_auto_resize(true);
+    let mut narrow: Histogram<u16> = HistogramConfig::new(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS)
+        .build_with_counter()
+        .unwrap();
+    for value in &[TEST_VALUE_LEVEL, 1_000] {
+        histogram.record_value(*value);
+        resized.record_value(*value);
+        narrow.record_value(*value);
+    }
+    resized.record_value(HIGHEST_TRACKABLE_VALUE);
+
+    assert!(histogram == narrow);
+    assert!(histogram != resized);
+
+    let mut shrunk = new_histogram(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    shrunk.set_auto_resize(true);
+    shrunk.record_values(&[TEST_VALUE_LEVEL, 1_000]);
+    assert!(shrunk.get_counts_array_length() < histogram.get_counts_array_length());
+    assert!(histogram == shrunk);
+    assert!(shrunk == histogram);
+    assert!(histogram != new_histogram_lower_bound(2, HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS));
+}
+
+#[test]
+fn test_scaled_values() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(1_000);
+    histogram.record_value(2_000);
+
+    assert_eq!(1.0, histogram.get_integer_to_double_value_conversion_ratio());
+    assert_eq!(histogram.get_mean(), histogram.get_scaled_mean());
+
+    histogram.set_integer_to_double_value_conversion_ratio(0.001);
+
+    assert_eq!(1.0, histogram.get_scaled_min_value());
+    assert_eq!(2.0, histogram.get_scaled_max_value());
+    assert_eq!(1.5, histogram.get_scaled_mean());
+    assert_eq!(0.5, histogram.get_scaled_std_deviation());
+    assert_eq!(1.0, histogram.get_scaled_value_at_percentile(50.0));
+    assert_eq!(histogram.get_value_at_percentile(100.0) as f64 * 0.001,
+               histogram.get_scaled_value_at_percentile(100.0));
+
+    let mut unscaled = histogram.clone();
+    assert!(unscaled == histogram);
+    unscaled.set_integer_to_double_value_conversion_ratio(1.0);
+    assert!(unscaled != histogram);
+}
+
+#[test]
+fn test_add_merges_time_range() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    histogram.record_value(TEST_VALUE_LEVEL);
+    histogram.set_start_time(2_000);
+    histogram.set_end_time(3_000);
+    histogram.set_tag(Some("a".to_string()));
+    let mut other = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    other.record_value(TEST_VALUE_LEVEL);
+    other.set_start_time(1_000);
+    other.set_end_time(2_500);
+    other.set_tag(Some("b".to_string()));
+
+    histogram.add(&other).unwrap();
+
+    assert_eq!(1_000, histogram.get_start_time());
+    assert_eq!(3_000, histogram.get_end_time());
+    assert_eq!(Some("a"), histogram.get_tag());
+
+    let mut empty = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    empty.add(&histogram).unwrap();
+
+    assert_eq!(1_000, empty.get_start_time());
+    assert_eq!(3_000, empty.get_end_time());
+    assert_eq!(Some("a"), histogram.clone().get_tag());
+}
+
 fn verify_max_value(histogram: Histogram) {
     let mut computed_max_value: i64 = 0;
     for i in 0..histogram.get_counts_array_length() {
@@ -94,5 +690,3 @@ fn verify_max_value(histogram: Histogram) {
 
     assert_eq!(computed_max_value, histogram.get_max_value());
 }
-
-
@@ -0,0 +1,88 @@
+extern crate rustogram;
+const HIGHEST_TRACKABLE_VALUE: i64 = 3600 * 1000 * 1000;
+const NUMBER_OF_SIGNIFICANT_VALUE_DIGITS: i32 = 3;
+
+use rustogram::counter::*;
+use rustogram::histogram::*;
+use rustogram::packed::*;
+
+#[test]
+fn test_packed_histogram_matches_histogram() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut packed_histogram = new_packed_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    for value in &[1, 1_000, 1_000, 123_456, 100_000_000, 0] {
+        histogram.record_value(*value);
+        packed_histogram.record_value(*value);
+    }
+    packed_histogram.record_value_with_expected_interval(10_000, 1_000);
+    histogram.record_value_with_expected_interval(10_000, 1_000);
+
+    assert_eq!(histogram.get_counts_array_length(), packed_histogram.get_counts_array_length());
+    assert_eq!(histogram.get_total_count(), packed_histogram.get_total_count());
+    assert_eq!(histogram.get_min_value(), packed_histogram.get_min_value());
+    assert_eq!(histogram.get_max_value(), packed_histogram.get_max_value());
+    assert_eq!(histogram.get_mean(), packed_histogram.get_mean());
+    assert_eq!(histogram.get_std_deviation(), packed_histogram.get_std_deviation());
+    assert_eq!(histogram.get_value_at_percentile(99.0), packed_histogram.get_value_at_percentile(99.0));
+    assert_eq!(3, packed_histogram.get_count_at_value(1_000));
+
+    let mut recorded_values = Vec::new();
+    let mut packed_recorded_values = Vec::new();
+    histogram.collect_recorded_values(&mut recorded_values);
+    packed_histogram.collect_recorded_values(&mut packed_recorded_values);
+    assert!(recorded_values == packed_recorded_values);
+}
+
+#[test]
+fn test_serialisation_matches_histogram() {
+    let mut histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let mut packed_histogram = new_packed_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    for value in 0..1_000 {
+        histogram.record_value(value * 1_000);
+        packed_histogram.record_value(value * 1_000);
+    }
+
+    let mut buffer = Vec::new();
+    let mut packed_buffer = Vec::new();
+    histogram.serialise(&mut buffer);
+    packed_histogram.serialise(&mut packed_buffer);
+    assert_eq!(buffer, packed_buffer);
+
+    let deserialised = deserialise_packed_histogram(&buffer, 0).unwrap();
+    assert_eq!(1_000, deserialised.get_total_count());
+    assert_eq!(1, deserialised.get_count_at_value(999_000));
+    assert_eq!(histogram.get_mean(), deserialised.get_mean());
+}
+
+#[test]
+fn test_auto_resize_and_reset() {
+    let config = HistogramConfig::new(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS).auto_resize(true);
+    let mut histogram: PackedHistogram = config.build_with_storage().unwrap();
+
+    histogram.record_value(HIGHEST_TRACKABLE_VALUE);
+    assert_eq!(1, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+    assert!(histogram.get_highest_trackable_value() >= HIGHEST_TRACKABLE_VALUE);
+
+    histogram.reset();
+    assert_eq!(0, histogram.get_total_count());
+    assert_eq!(0, histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+}
+
+#[test]
+fn test_packed_counts_only_store_populated_indexes() {
+    let mut counts: PackedCounts<u32> = CountsArray::with_length(100_000);
+
+    counts.set(99_999, 3);
+    counts.set(10, 1);
+    counts.set(500, 2);
+    counts.set(500, 0);
+
+    assert_eq!(100_000, counts.length());
+    assert_eq!(2, counts.get_populated_length());
+    assert_eq!(3, counts.get(99_999));
+    assert_eq!(0, counts.get(500));
+
+    counts.resize(50_000);
+    assert_eq!(1, counts.get_populated_length());
+    assert_eq!(1, counts.get(10));
+}
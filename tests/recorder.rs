@@ -0,0 +1,164 @@
+extern crate rustogram;
+const HIGHEST_TRACKABLE_VALUE: i64 = 3600 * 1000 * 1000;
+const NUMBER_OF_SIGNIFICANT_VALUE_DIGITS: i32 = 3;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use rustogram::histogram::*;
+use rustogram::recorder::*;
+
+#[test]
+fn test_get_interval_histogram() {
+    let recorder = new_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    recorder.record_value(1_000);
+    recorder.record_value_with_expected_interval(1_000, 100);
+
+    let interval_histogram = recorder.get_interval_histogram(None);
+
+    assert_eq!(11, interval_histogram.get_total_count());
+    assert_eq!(2, interval_histogram.get_count_at_value(1_000));
+    assert_eq!(100, interval_histogram.get_min_value());
+    assert!(interval_histogram.get_start_time() > 0);
+    assert!(interval_histogram.get_end_time() >= interval_histogram.get_start_time());
+
+    recorder.record_value(2_000);
+    let next_interval_histogram = recorder.get_interval_histogram(None);
+
+    assert_eq!(1, next_interval_histogram.get_total_count());
+    assert_eq!(1, next_interval_histogram.get_count_at_value(2_000));
+    assert_eq!(interval_histogram.get_end_time(), next_interval_histogram.get_start_time());
+}
+
+#[test]
+fn test_recycled_interval_histogram_is_reset() {
+    let recorder = new_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    recorder.record_value(1_000);
+    let interval_histogram = recorder.get_interval_histogram(None);
+
+    recorder.record_value(2_000);
+    let recycled_histogram = recorder.get_interval_histogram(Some(interval_histogram));
+
+    assert_eq!(1, recycled_histogram.get_total_count());
+    assert_eq!(0, recycled_histogram.get_count_at_value(1_000));
+    assert_eq!(1, recycled_histogram.get_count_at_value(2_000));
+    assert_eq!(2_000, recycled_histogram.get_min_value());
+}
+
+#[test]
+fn test_bad_recycle_keeps_interval() {
+    let recorder = new_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    recorder.record_value(1_000);
+
+    let mismatched_histogram = new_histogram(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS - 1);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| recorder.get_interval_histogram(Some(mismatched_histogram))));
+    assert!(result.is_err());
+
+    let interval_histogram = recorder.get_interval_histogram(None);
+    assert_eq!(1, interval_histogram.get_total_count());
+    assert_eq!(1, interval_histogram.get_count_at_value(1_000));
+}
+
+#[test]
+fn test_reset() {
+    let recorder = new_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    recorder.record_value(1_000);
+
+    recorder.reset();
+
+    assert_eq!(0, recorder.get_interval_histogram(None).get_total_count());
+}
+
+#[test]
+fn test_auto_resizing_recorder() {
+    let config = HistogramConfig::new(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS).auto_resize(true);
+    let recorder = Recorder::from_config(&config).unwrap();
+    recorder.record_value(1_000);
+    recorder.record_value(HIGHEST_TRACKABLE_VALUE);
+
+    let interval_histogram = recorder.get_interval_histogram(None);
+
+    assert_eq!(2, interval_histogram.get_total_count());
+    assert_eq!(1, interval_histogram.get_count_at_value(HIGHEST_TRACKABLE_VALUE));
+    assert!(interval_histogram.get_highest_trackable_value() >= HIGHEST_TRACKABLE_VALUE);
+    assert!(Recorder::from_config(&HistogramConfig::new(2, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS))
+                .unwrap()
+                .try_record_value(HIGHEST_TRACKABLE_VALUE)
+                .is_err());
+}
+
+#[test]
+fn test_interval_histograms_while_recording() {
+    let recorder = Arc::new(new_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let recorder = recorder.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    recorder.record_value(1_000);
+                }
+            })
+        })
+        .collect();
+
+    let mut total_count = 0;
+    let mut interval_histogram = None;
+    while handles.iter().any(|handle| !handle.is_finished()) {
+        let histogram = recorder.get_interval_histogram(interval_histogram.take());
+        assert_eq!(histogram.get_total_count(), histogram.get_count_at_value(1_000));
+        total_count += histogram.get_total_count();
+        interval_histogram = Some(histogram);
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    total_count += recorder.get_interval_histogram(interval_histogram).get_total_count();
+
+    assert_eq!(40_000, total_count);
+}
+
+#[test]
+fn test_single_writer_get_interval_histogram() {
+    let recorder = new_single_writer_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    recorder.record_value(1_000);
+    recorder.record_value_with_expected_interval(1_000, 100);
+
+    let interval_histogram = recorder.get_interval_histogram(None);
+
+    assert_eq!(11, interval_histogram.get_total_count());
+    assert_eq!(2, interval_histogram.get_count_at_value(1_000));
+    assert!(interval_histogram.get_end_time() >= interval_histogram.get_start_time());
+
+    recorder.record_value(2_000);
+    let recycled_histogram = recorder.get_interval_histogram(Some(interval_histogram));
+
+    assert_eq!(1, recycled_histogram.get_total_count());
+    assert_eq!(0, recycled_histogram.get_count_at_value(1_000));
+    assert_eq!(2_000, recycled_histogram.get_min_value());
+}
+
+#[test]
+fn test_single_writer_interval_histograms_while_recording() {
+    let recorder = new_single_writer_recorder(HIGHEST_TRACKABLE_VALUE, NUMBER_OF_SIGNIFICANT_VALUE_DIGITS);
+    let reader = recorder.interval_reader();
+
+    let writer = thread::spawn(move || {
+        for _ in 0..100_000 {
+            recorder.record_value(1_000);
+        }
+    });
+
+    let mut total_count = 0;
+    let mut interval_histogram = None;
+    while !writer.is_finished() {
+        let histogram = reader.get_interval_histogram(interval_histogram.take());
+        assert_eq!(histogram.get_total_count(), histogram.get_count_at_value(1_000));
+        total_count += histogram.get_total_count();
+        interval_histogram = Some(histogram);
+    }
+    writer.join().unwrap();
+    total_count += reader.get_interval_histogram(interval_histogram).get_total_count();
+
+    assert_eq!(100_000, total_count);
+}
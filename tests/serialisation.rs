@@ -1,6 +1,6 @@
 extern crate rustogram;
 extern crate rustc_serialize;
-static SERIALISED_FORM: &'static str = "HISTEwAAAAQAAAAAAAAABQAAAAABMS0AAAAAAAX14QA/8AAAAAAAAAAEBQI=";
+static SERIALISED_FORM: &str = "HISTEwAAAAQAAAAAAAAABQAAAAABMS0AAAAAAAX14QA/8AAAAAAAAAAEBQI=";
 
 use rustogram::encoding::*;
 use rustogram::histogram::*;
@@ -52,10 +52,10 @@ fn test_sanity() {
 fn test_zig_zag_encoding_for_limits() {
 	let mut buffer : Vec<u8> = Vec::new();
 	
-	let value0: i64 = std::i64::MAX;
+	let value0: i64 = i64::MAX;
 	encode(value0, &mut buffer);
 	
-	let value1: i64 = std::i64::MIN;
+	let value1: i64 = i64::MIN;
 	encode(value1, &mut buffer);
 	
 	print_byte_vec(&buffer);
@@ -77,8 +77,8 @@ fn test_i32_encoding_and_decoding() {
 
 #[test]
 fn test_i32_encoding_and_decoding_limits() {
-	assert_i32_encoding_and_decoding(std::i32::MAX);
-	assert_i32_encoding_and_decoding(std::i32::MIN);
+	assert_i32_encoding_and_decoding(i32::MAX);
+	assert_i32_encoding_and_decoding(i32::MIN);
 }
 
 #[test]
@@ -88,8 +88,8 @@ fn test_i64_encoding_and_decoding() {
 
 #[test]
 fn test_i64_encoding_and_decoding_limits() {
-	assert_i64_encoding_and_decoding(std::i64::MAX);
-	assert_i64_encoding_and_decoding(std::i64::MIN);
+	assert_i64_encoding_and_decoding(i64::MAX);
+	assert_i64_encoding_and_decoding(i64::MIN);
 }
 
 #[test]
@@ -118,13 +118,144 @@ fn test_deserialise() {
     assert!(histogram.values_are_equivalent(30000000, histogram.get_value_at_percentile(83.33)));
     assert!(histogram.values_are_equivalent(100000000, histogram.get_value_at_percentile(83.34)));
     assert!(histogram.values_are_equivalent(100000000, histogram.get_value_at_percentile(99.0)));
+    assert_eq!(1.0, deserialised_histogram.get_integer_to_double_value_conversion_ratio());
+}
+
+#[test]
+fn test_serialise_conversion_ratio() {
+    let mut histogram = new_histogram(3_600_000_000, 3);
+    histogram.set_integer_to_double_value_conversion_ratio(0.001);
+    histogram.record_value(1_500);
+    histogram.record_value(2_500_000);
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+
+    let deserialised_histogram = deserialise_histogram(&target_buffer, 0).unwrap();
+
+    assert_eq!(0.001, deserialised_histogram.get_integer_to_double_value_conversion_ratio());
+    assert_eq!(histogram.get_scaled_mean(), deserialised_histogram.get_scaled_mean());
+    assert_eq!(histogram.get_scaled_value_at_percentile(99.0), deserialised_histogram.get_scaled_value_at_percentile(99.0));
+    assert!(histogram == deserialised_histogram);
+}
+
+#[test]
+fn test_serialise_auto_resized_histogram() {
+    let mut histogram = new_histogram(1_000, 3);
+    histogram.set_auto_resize(true);
+    histogram.record_value(42);
+    histogram.record_value(10_000_000);
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+
+    let deserialised_histogram = deserialise_histogram(&target_buffer, 0).unwrap();
+
+    assert_eq!(histogram.get_highest_trackable_value(), deserialised_histogram.get_highest_trackable_value());
+    assert_eq!(histogram.get_total_count(), deserialised_histogram.get_total_count());
+    assert_eq!(1, deserialised_histogram.get_count_at_value(42));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(10_000_000));
+    assert!(histogram.values_are_equivalent(histogram.get_max_value(), deserialised_histogram.get_max_value()));
+    assert!(histogram == deserialised_histogram);
+}
+
+#[test]
+fn test_serialise_shifted_histogram() {
+    let mut histogram = new_histogram(3_600_000_000, 3);
+    histogram.record_value(0);
+    histogram.record_value(1_500);
+    histogram.record_value(2_500_000);
+    histogram.shift_values_left(3).unwrap();
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+
+    let deserialised_histogram = deserialise_histogram(&target_buffer, 0).unwrap();
+
+    assert_eq!(3 * 1024, histogram.get_normalizing_index_offset());
+    assert_eq!(histogram.get_normalizing_index_offset(), get_i32(&target_buffer, 8));
+    assert_eq!(histogram.get_normalizing_index_offset(), deserialised_histogram.get_normalizing_index_offset());
+    assert_eq!(1, deserialised_histogram.get_count_at_value(0));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(1_500 << 3));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(2_500_000 << 3));
+    assert!(histogram == deserialised_histogram);
+}
+
+#[test]
+fn test_deserialise_with_normalising_index_offset() {
+    let mut histogram = new_histogram(3_600_000_000, 3);
+    histogram.record_value(1_500);
+    histogram.record_value(2_500_000);
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+    // the payload lists counts by index, regardless of how they were stored by the writer
+    put_i32_at_offset(-2 * 1024, &mut target_buffer, 8);
+
+    let mut deserialised_histogram = deserialise_histogram(&target_buffer, 0).unwrap();
+
+    assert_eq!(1, deserialised_histogram.get_count_at_value(1_500));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(2_500_000));
+    assert!(histogram == deserialised_histogram);
+
+    deserialised_histogram.shift_values_left(2).unwrap();
+
+    assert_eq!(0, deserialised_histogram.get_normalizing_index_offset());
+    assert_eq!(1, deserialised_histogram.get_count_at_value(1_500 << 2));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(2_500_000 << 2));
+}
+
+#[test]
+fn test_serialise_narrow_counter_histograms() {
+    let mut histogram: Histogram<u16> = HistogramConfig::new(100_000_000, 3).build_with_counter().unwrap();
+    histogram.try_record_value_with_count(1_000, 300).unwrap();
+    histogram.record_value(20_000_000);
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+
+    let as_u16: Histogram<u16> = deserialise_histogram_with_counter(&target_buffer, 0).unwrap();
+    let as_u64 = deserialise_histogram(&target_buffer, 0).unwrap();
+
+    assert_eq!(301, as_u16.get_total_count());
+    assert_eq!(300, as_u16.get_count_at_value(1_000));
+    assert_eq!(301, as_u64.get_total_count());
+    assert_eq!(300, as_u64.get_count_at_value(1_000));
+}
+
+#[test]
+fn test_deserialise_into_too_narrow_counter() {
+    let mut histogram = new_histogram(100_000_000, 3);
+    histogram.try_record_value_with_count(1_000, 300).unwrap();
+
+    let mut target_buffer: Vec<u8> = Vec::new();
+    histogram.serialise(&mut target_buffer);
+
+    assert!(deserialise_histogram_with_counter::<u8>(&target_buffer, 0).is_none());
+}
+
+#[test]
+fn test_deserialise_at_offset() {
+    let mut histogram = new_histogram(100_000_000, 3);
+    histogram.record_value(1_000);
+    histogram.record_value(20_000_000);
+
+    let mut target_buffer: Vec<u8> = vec![0xff; 7];
+    histogram.serialise(&mut target_buffer);
+
+    let deserialised_histogram = deserialise_histogram(&target_buffer, 7).unwrap();
+
+    assert_eq!(2, deserialised_histogram.get_total_count());
+    assert_eq!(1, deserialised_histogram.get_count_at_value(1_000));
+    assert_eq!(1, deserialised_histogram.get_count_at_value(20_000_000));
+    assert!(histogram == deserialised_histogram);
 }
 
 fn print_byte_vec(buffer: &Vec<u8>) {
 	for b in buffer {
 		print!("{}{} ", char_for_nibble(b / 16), char_for_nibble(b % 16));
 	}
-	println!("");
+	println!();
 }
 
 fn char_for_nibble(input: u8) -> &'static str {